@@ -16,5 +16,9 @@ echo "Program ID: $PROGRAM_ID"
 sed -i "s/declare_id!(\"[^\"]*\")/declare_id!(\"$PROGRAM_ID\")/" programs/kangklip_credits/src/lib.rs
 sed -i "s/kangklip_credits = \"[^\"]*\"/kangklip_credits = \"$PROGRAM_ID\"/" Anchor.toml
 
-anchor build --no-idl
+if [ "${DEPLOY_NETWORK:-devnet}" = "devnet" ]; then
+  anchor build --no-idl -- --features devnet-faucet
+else
+  anchor build --no-idl
+fi
 anchor deploy
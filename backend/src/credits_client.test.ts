@@ -0,0 +1,109 @@
+import assert from "node:assert/strict";
+import { readFileSync } from "node:fs";
+import { test } from "node:test";
+import { Keypair } from "@solana/web3.js";
+import {
+  CONSUME_CREDIT_OPTIONAL_ACCOUNTS,
+  PAY_STABLE_OPTIONAL_ACCOUNTS,
+  buildConsumeCreditInstruction,
+  buildPayStableInstruction,
+} from "./credits_client.js";
+
+const PROGRAM_SOURCE = readFileSync(
+  new URL("../../programs/kangklip_credits/src/lib.rs", import.meta.url),
+  "utf-8"
+);
+
+// Field names of an Accounts struct in the on-chain program, in declaration order.
+const accountsStructFields = (name: string): string[] => {
+  const start = PROGRAM_SOURCE.indexOf(`pub struct ${name}<'info> {`);
+  assert.notEqual(start, -1, `missing accounts struct ${name}`);
+  const body = PROGRAM_SOURCE.slice(start, PROGRAM_SOURCE.indexOf("\n}\n", start));
+  return [...body.matchAll(/^\s+pub (\w+):/gm)].map((match) => match[1]);
+};
+
+const key = () => Keypair.generate().publicKey;
+
+test("pay_stable passes every PayStable account, placeholders included", () => {
+  const programId = key();
+  const fields = accountsStructFields("PayStable");
+  const instruction = buildPayStableInstruction({
+    programId,
+    user: key(),
+    config: key(),
+    userCredit: key(),
+    userToken: key(),
+    vaultToken: key(),
+    mint: key(),
+    amountBaseUnits: 1_000_000n,
+  });
+
+  assert.equal(instruction.keys.length, fields.length);
+  assert.deepEqual(fields.slice(-PAY_STABLE_OPTIONAL_ACCOUNTS.length), [
+    ...PAY_STABLE_OPTIONAL_ACCOUNTS,
+  ]);
+  for (const meta of instruction.keys.slice(-PAY_STABLE_OPTIONAL_ACCOUNTS.length)) {
+    assert.ok(meta.pubkey.equals(programId));
+    assert.equal(meta.isWritable, false);
+  }
+});
+
+test("pay_stable places a supplied journal in its slot", () => {
+  const programId = key();
+  const journal = key();
+  const instruction = buildPayStableInstruction({
+    programId,
+    user: key(),
+    config: key(),
+    userCredit: key(),
+    userToken: key(),
+    vaultToken: key(),
+    mint: key(),
+    amountBaseUnits: 1_000_000n,
+    optionalAccounts: { journal },
+  });
+
+  const fields = accountsStructFields("PayStable");
+  const meta = instruction.keys[fields.indexOf("journal")];
+  assert.ok(meta.pubkey.equals(journal));
+  assert.equal(meta.isWritable, true);
+});
+
+test("consume_credit passes every ConsumeCredit account, placeholders included", () => {
+  const programId = key();
+  const fields = accountsStructFields("ConsumeCredit");
+  const instruction = buildConsumeCreditInstruction({
+    programId,
+    spender: key(),
+    config: key(),
+    user: key(),
+    userCredit: key(),
+    amount: 1n,
+  });
+
+  assert.equal(instruction.keys.length, fields.length);
+  assert.deepEqual(fields.slice(-CONSUME_CREDIT_OPTIONAL_ACCOUNTS.length), [
+    ...CONSUME_CREDIT_OPTIONAL_ACCOUNTS,
+  ]);
+  for (const meta of instruction.keys.slice(-CONSUME_CREDIT_OPTIONAL_ACCOUNTS.length)) {
+    assert.ok(meta.pubkey.equals(programId));
+  }
+});
+
+test("consume_credit marks a supplied co-spender as a signer", () => {
+  const coSpender = key();
+  const instruction = buildConsumeCreditInstruction({
+    programId: key(),
+    spender: key(),
+    config: key(),
+    user: key(),
+    userCredit: key(),
+    amount: 1n,
+    optionalAccounts: { co_spender: coSpender },
+  });
+
+  const fields = accountsStructFields("ConsumeCredit");
+  const meta = instruction.keys[fields.indexOf("co_spender")];
+  assert.ok(meta.pubkey.equals(coSpender));
+  assert.equal(meta.isSigner, true);
+});
@@ -1,5 +1,10 @@
 import crypto from "crypto";
-import { PublicKey, TransactionInstruction } from "@solana/web3.js";
+import {
+  AccountMeta,
+  PublicKey,
+  SystemProgram,
+  TransactionInstruction,
+} from "@solana/web3.js";
 
 export const CREDIT_UNIT = 100000;
 
@@ -8,6 +13,37 @@ const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey(
   "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
 );
 
+// Trailing Option<> accounts of PayStable, in struct order. Anchor reads them
+// positionally, so every slot must be present; unused ones carry the program ID.
+export const PAY_STABLE_OPTIONAL_ACCOUNTS = [
+  "price_update",
+  "instructions",
+  "platform_vault",
+  "journal",
+  "price_tiers",
+  "voucher",
+  "referrer_credit",
+  "region_vault",
+  "campaign",
+  "membership_token",
+  "membership_metadata",
+  "holder_tiers",
+  "holder_token",
+  "price_feed",
+] as const;
+export type PayStableOptionalAccount = (typeof PAY_STABLE_OPTIONAL_ACCOUNTS)[number];
+const PAY_STABLE_WRITABLE_ACCOUNTS: ReadonlySet<string> = new Set([
+  "platform_vault",
+  "journal",
+  "voucher",
+  "referrer_credit",
+  "price_feed",
+]);
+
+// Trailing Option<> accounts of ConsumeCredit, in struct order.
+export const CONSUME_CREDIT_OPTIONAL_ACCOUNTS = ["co_spender", "journal"] as const;
+export type ConsumeCreditOptionalAccount = (typeof CONSUME_CREDIT_OPTIONAL_ACCOUNTS)[number];
+
 // Anchor instruction discriminator (first 8 bytes of sha256(name)).
 const anchorDiscriminator = (name: string): Buffer => {
   return crypto.createHash("sha256").update(name).digest().subarray(0, 8);
@@ -22,6 +58,43 @@ export const deriveConfigPda = (authority: PublicKey, programId: PublicKey): Pub
   return pda;
 };
 
+// Derive the price feed PDA that publishes a config's effective pricing.
+export const derivePriceFeedPda = (config: PublicKey, programId: PublicKey): PublicKey => {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("price_feed"), config.toBuffer()],
+    programId
+  );
+  return pda;
+};
+
+// Derive the program-wide global state PDA.
+export const deriveGlobalPda = (programId: PublicKey): PublicKey => {
+  const [pda] = PublicKey.findProgramAddressSync([Buffer.from("global")], programId);
+  return pda;
+};
+
+// Derive the config's journal ring buffer PDA.
+export const deriveJournalPda = (config: PublicKey, programId: PublicKey): PublicKey => {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("journal"), config.toBuffer()],
+    programId
+  );
+  return pda;
+};
+
+// Derive the accepted-mint registry PDA for a config + mint.
+export const deriveAcceptedMintPda = (
+  config: PublicKey,
+  mint: PublicKey,
+  programId: PublicKey
+): PublicKey => {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("accepted_mint"), config.toBuffer(), mint.toBuffer()],
+    programId
+  );
+  return pda;
+};
+
 // Derive the user credit PDA from wallet.
 export const deriveUserCreditPda = (user: PublicKey, programId: PublicKey): PublicKey => {
   const [pda] = PublicKey.findProgramAddressSync(
@@ -31,6 +104,19 @@ export const deriveUserCreditPda = (user: PublicKey, programId: PublicKey): Publ
   return pda;
 };
 
+// Derive the external ID mapping PDA from a salted ID hash (32 bytes).
+export const deriveExternalIdPda = (
+  config: PublicKey,
+  idHash: Buffer,
+  programId: PublicKey
+): PublicKey => {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("external_id"), config.toBuffer(), idHash],
+    programId
+  );
+  return pda;
+};
+
 // Derive an ATA for a given owner + mint.
 export const deriveAssociatedTokenAddress = (owner: PublicKey, mint: PublicKey): PublicKey => {
   const [ata] = PublicKey.findProgramAddressSync(
@@ -40,12 +126,14 @@ export const deriveAssociatedTokenAddress = (owner: PublicKey, mint: PublicKey):
   return ata;
 };
 
-// Build Anchor instruction data for pay_usdc.
-export const buildPayUsdcInstructionData = (amountBaseUnits: bigint): Buffer => {
-  const discriminator = anchorDiscriminator("global:pay_usdc");
+// Build Anchor instruction data for pay_stable.
+export const buildPayStableInstructionData = (amountBaseUnits: bigint): Buffer => {
+  const discriminator = anchorDiscriminator("global:pay_stable");
   const amountBuffer = Buffer.alloc(8);
   amountBuffer.writeBigUInt64LE(amountBaseUnits);
-  return Buffer.concat([discriminator, amountBuffer]);
+  // voucher_code: Option<String> = None
+  const voucherCode = Buffer.from([0]);
+  return Buffer.concat([discriminator, amountBuffer, voucherCode]);
 };
 
 // Build Anchor instruction data for consume_credit.
@@ -56,18 +144,49 @@ export const buildConsumeCreditInstructionData = (amount: bigint): Buffer => {
   return Buffer.concat([discriminator, amountBuffer]);
 };
 
-// Build a full pay_usdc transaction instruction.
-export const buildPayUsdcInstruction = (params: {
+// Fill a run of optional accounts, passing the program ID for any slot left unset.
+const buildOptionalAccountMetas = <Name extends string>(
+  programId: PublicKey,
+  names: readonly Name[],
+  accounts: Partial<Record<Name, PublicKey>>,
+  writable: ReadonlySet<string>,
+  signers: ReadonlySet<string> = new Set()
+): AccountMeta[] => {
+  return names.map((name) => {
+    const pubkey = accounts[name];
+    if (!pubkey) {
+      return { pubkey: programId, isSigner: false, isWritable: false };
+    }
+    return { pubkey, isSigner: signers.has(name), isWritable: writable.has(name) };
+  });
+};
+
+// Build the trailing optional account metas of a pay_stable instruction.
+export const buildPayStableOptionalAccountMetas = (
+  programId: PublicKey,
+  accounts: Partial<Record<PayStableOptionalAccount, PublicKey>> = {}
+): AccountMeta[] => {
+  return buildOptionalAccountMetas(
+    programId,
+    PAY_STABLE_OPTIONAL_ACCOUNTS,
+    accounts,
+    PAY_STABLE_WRITABLE_ACCOUNTS
+  );
+};
+
+// Build a full pay_stable transaction instruction.
+export const buildPayStableInstruction = (params: {
   programId: PublicKey;
   user: PublicKey;
   config: PublicKey;
   userCredit: PublicKey;
-  userUsdc: PublicKey;
-  vaultUsdc: PublicKey;
-  usdcMint: PublicKey;
+  userToken: PublicKey;
+  vaultToken: PublicKey;
+  mint: PublicKey;
   amountBaseUnits: bigint;
+  optionalAccounts?: Partial<Record<PayStableOptionalAccount, PublicKey>>;
 }): TransactionInstruction => {
-  const data = buildPayUsdcInstructionData(params.amountBaseUnits);
+  const data = buildPayStableInstructionData(params.amountBaseUnits);
   return new TransactionInstruction({
     programId: params.programId,
     data,
@@ -75,10 +194,18 @@ export const buildPayUsdcInstruction = (params: {
       { pubkey: params.user, isSigner: true, isWritable: true },
       { pubkey: params.config, isSigner: false, isWritable: true },
       { pubkey: params.userCredit, isSigner: false, isWritable: true },
-      { pubkey: params.userUsdc, isSigner: false, isWritable: true },
-      { pubkey: params.vaultUsdc, isSigner: false, isWritable: true },
-      { pubkey: params.usdcMint, isSigner: false, isWritable: false },
+      { pubkey: params.userToken, isSigner: false, isWritable: true },
+      { pubkey: params.vaultToken, isSigner: false, isWritable: true },
+      { pubkey: params.mint, isSigner: false, isWritable: false },
+      {
+        pubkey: deriveAcceptedMintPda(params.config, params.mint, params.programId),
+        isSigner: false,
+        isWritable: false,
+      },
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: deriveGlobalPda(params.programId), isSigner: false, isWritable: false },
+      ...buildPayStableOptionalAccountMetas(params.programId, params.optionalAccounts),
     ],
   });
 };
@@ -91,6 +218,7 @@ export const buildConsumeCreditInstruction = (params: {
   user: PublicKey;
   userCredit: PublicKey;
   amount: bigint;
+  optionalAccounts?: Partial<Record<ConsumeCreditOptionalAccount, PublicKey>>;
 }): TransactionInstruction => {
   const data = buildConsumeCreditInstructionData(params.amount);
   return new TransactionInstruction({
@@ -98,9 +226,17 @@ export const buildConsumeCreditInstruction = (params: {
     data,
     keys: [
       { pubkey: params.spender, isSigner: true, isWritable: true },
-      { pubkey: params.config, isSigner: false, isWritable: false },
+      { pubkey: params.config, isSigner: false, isWritable: true },
       { pubkey: params.user, isSigner: false, isWritable: false },
       { pubkey: params.userCredit, isSigner: false, isWritable: true },
+      { pubkey: deriveGlobalPda(params.programId), isSigner: false, isWritable: false },
+      ...buildOptionalAccountMetas(
+        params.programId,
+        CONSUME_CREDIT_OPTIONAL_ACCOUNTS,
+        params.optionalAccounts ?? {},
+        new Set(["journal"]),
+        new Set(["co_spender"])
+      ),
     ],
   });
 };
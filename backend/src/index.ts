@@ -20,12 +20,19 @@ import { getObjectStream, loadManifest, signObjectUrl } from "./r2.js";
 import {
   CREDIT_UNIT,
   buildConsumeCreditInstruction,
-  buildPayUsdcInstructionData,
+  buildPayStableInstructionData,
+  buildPayStableOptionalAccountMetas,
   deriveAssociatedTokenAddress,
+  deriveAcceptedMintPda,
   deriveConfigPda,
+  deriveGlobalPda,
+  deriveJournalPda,
+  derivePriceFeedPda,
   deriveUserCreditPda,
 } from "./credits_client.js";
 import {
+  decodeConfigCreditUnit,
+  decodePriceFeedCreditUnit,
   decodeUserCreditAccount,
   fetchAccountData,
   fetchParsedTransaction,
@@ -189,6 +196,33 @@ const fetchOnchainCredits = async (walletAddress: string): Promise<number> => {
   return Number.isFinite(credits) ? credits : 0;
 };
 
+// Reads the effective credit unit from the price feed, which tracks the curve step in
+// curve mode; falls back to the Config PDA's flat credit_unit, then to the default.
+const fetchOnchainCreditUnit = async (
+  configPda: PublicKey,
+  programId: PublicKey
+): Promise<number> => {
+  const priceFeedPda = derivePriceFeedPda(configPda, programId);
+  const feedData = await fetchAccountData(config.solanaRpcUrl, priceFeedPda.toBase58());
+  const feedUnit = feedData ? decodePriceFeedCreditUnit(feedData) : null;
+  if (feedUnit) {
+    return Number(feedUnit);
+  }
+  const accountData = await fetchAccountData(config.solanaRpcUrl, configPda.toBase58());
+  const creditUnit = accountData ? decodeConfigCreditUnit(accountData) : null;
+  return creditUnit ? Number(creditUnit) : CREDIT_UNIT;
+};
+
+// Return the config's journal PDA once it exists, so instructions can record into it.
+const fetchJournalAccount = async (
+  configPda: PublicKey,
+  programId: PublicKey
+): Promise<PublicKey | undefined> => {
+  const journalPda = deriveJournalPda(configPda, programId);
+  const journalData = await fetchAccountData(config.solanaRpcUrl, journalPda.toBase58());
+  return journalData ? journalPda : undefined;
+};
+
 // Sends a consume_credit instruction signed by the backend spender key.
 const MEMO_PROGRAM_ID = new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
 
@@ -218,6 +252,7 @@ const consumeOnchainCredit = async (
   const userKey = new PublicKey(walletAddress);
   const configPda = deriveConfigPda(authority, programId);
   const userCreditPda = deriveUserCreditPda(userKey, programId);
+  const journal = await fetchJournalAccount(configPda, programId);
   const instruction = buildConsumeCreditInstruction({
     programId,
     spender: spenderKeypair.publicKey,
@@ -225,6 +260,7 @@ const consumeOnchainCredit = async (
     user: userKey,
     userCredit: userCreditPda,
     amount: BigInt(amount),
+    optionalAccounts: { journal },
   });
   const tx = new Transaction();
   const safeMemo = buildMemo(memo);
@@ -822,7 +858,7 @@ app.get("/api/credits/balance", requireAuthToken, async (req: Request, res: Resp
   return res.json({ credits });
 });
 
-// Build a top-up intent with the pay_usdc instruction payload.
+// Build a top-up intent with the pay_stable instruction payload.
 app.post(
   "/api/credits/topup/usdc/intent",
   requireAuthToken,
@@ -836,27 +872,43 @@ app.post(
     if (!Number.isFinite(credits) || credits <= 0 || !Number.isInteger(credits)) {
       return res.status(400).json({ detail: "credits_to_buy must be a positive integer" });
     }
-    const amountBaseUnits = credits * CREDIT_UNIT;
     const programId = new PublicKey(config.creditsProgramId);
     const authority = new PublicKey(config.treasuryAddress);
     const usdcMint = new PublicKey(config.usdcMint);
     const walletKey = new PublicKey(authWallet);
     const configPda = deriveConfigPda(authority, programId);
+    const creditUnit = await fetchOnchainCreditUnit(configPda, programId);
+    const amountBaseUnits = credits * creditUnit;
     const userCreditPda = deriveUserCreditPda(walletKey, programId);
     const vaultAta = deriveAssociatedTokenAddress(configPda, usdcMint);
     const userAta = deriveAssociatedTokenAddress(walletKey, usdcMint);
-    const instructionData = buildPayUsdcInstructionData(BigInt(amountBaseUnits)).toString("base64");
+    const instructionData = buildPayStableInstructionData(BigInt(amountBaseUnits)).toString("base64");
+    const journal = await fetchJournalAccount(configPda, programId);
+    const priceFeedPda = derivePriceFeedPda(configPda, programId);
+    const priceFeed = (await fetchAccountData(config.solanaRpcUrl, priceFeedPda.toBase58()))
+      ? priceFeedPda
+      : undefined;
+    const optionalAccounts = buildPayStableOptionalAccountMetas(programId, {
+      journal,
+      price_feed: priceFeed,
+    });
     return res.json({
       wallet_address: authWallet,
       credits_to_buy: credits,
       amount_base_units: amountBaseUnits,
-      credit_unit: CREDIT_UNIT,
+      credit_unit: creditUnit,
       program_id: programId.toBase58(),
       config_pda: configPda.toBase58(),
+      global_pda: deriveGlobalPda(programId).toBase58(),
       user_credit_pda: userCreditPda.toBase58(),
       vault_ata: vaultAta.toBase58(),
       user_usdc_ata: userAta.toBase58(),
       usdc_mint: usdcMint.toBase58(),
+      accepted_mint_pda: deriveAcceptedMintPda(configPda, usdcMint, programId).toBase58(),
+      optional_accounts: optionalAccounts.map((meta) => ({
+        pubkey: meta.pubkey.toBase58(),
+        is_writable: meta.isWritable,
+      })),
       instruction_data: instructionData,
     });
   }
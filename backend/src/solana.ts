@@ -188,6 +188,38 @@ export const decodeUserCreditAccount = (data: Buffer): { user: string; credits:
   return { user, credits };
 };
 
+// Decodes credit_unit from the Config PDA account.
+export const decodeConfigCreditUnit = (data: Buffer): bigint | null => {
+  const discriminator = crypto
+    .createHash("sha256")
+    .update("account:Config")
+    .digest()
+    .subarray(0, 8);
+  if (data.length < 8 + 32 + 32 + 32 + 8) {
+    return null;
+  }
+  if (!data.subarray(0, 8).equals(discriminator)) {
+    return null;
+  }
+  return data.readBigUInt64LE(8 + 32 + 32 + 32);
+};
+
+// Decodes the effective credit_unit from a PriceFeed PDA account.
+export const decodePriceFeedCreditUnit = (data: Buffer): bigint | null => {
+  const discriminator = crypto
+    .createHash("sha256")
+    .update("account:PriceFeed")
+    .digest()
+    .subarray(0, 8);
+  if (data.length < 8 + 32 + 32 + 8) {
+    return null;
+  }
+  if (!data.subarray(0, 8).equals(discriminator)) {
+    return null;
+  }
+  return data.readBigUInt64LE(8 + 32 + 32);
+};
+
 // Loads a keypair from a JSON file path or JSON array string.
 export const loadKeypair = (source: string): Keypair => {
   const raw = source.trim();
@@ -14,10 +14,13 @@ type IntentResponse = {
   credit_unit: number;
   program_id: string;
   config_pda: string;
+  global_pda: string;
+  accepted_mint_pda: string;
   user_credit_pda: string;
   vault_ata: string;
   user_usdc_ata: string;
   usdc_mint: string;
+  optional_accounts: { pubkey: string; is_writable: boolean }[];
   instruction_data: string;
 };
 
@@ -81,7 +84,7 @@ export default function TopupPage() {
     refreshBalance();
   }, [refreshBalance]);
 
-  // Ask the backend for a pay_usdc instruction payload.
+  // Ask the backend for a pay_stable instruction payload.
   const requestIntent = async () => {
     const response = await fetch(`${apiBase}/api/credits/topup/usdc/intent`, {
       method: "POST",
@@ -95,7 +98,7 @@ export default function TopupPage() {
     return payload as IntentResponse;
   };
 
-  // Build the Anchor pay_usdc instruction from the backend payload.
+  // Build the Anchor pay_stable instruction from the backend payload.
   const buildInstruction = (intent: IntentResponse, walletAddress: PublicKey) => {
     return new TransactionInstruction({
       programId: new PublicKey(intent.program_id),
@@ -106,8 +109,16 @@ export default function TopupPage() {
         { pubkey: new PublicKey(intent.user_usdc_ata), isSigner: false, isWritable: true },
         { pubkey: new PublicKey(intent.vault_ata), isSigner: false, isWritable: true },
         { pubkey: new PublicKey(intent.usdc_mint), isSigner: false, isWritable: false },
+        { pubkey: new PublicKey(intent.accepted_mint_pda), isSigner: false, isWritable: false },
         { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: new PublicKey(intent.global_pda), isSigner: false, isWritable: false },
+        // Trailing Option<> slots, in struct order; unused ones carry the program ID.
+        ...intent.optional_accounts.map((account) => ({
+          pubkey: new PublicKey(account.pubkey),
+          isSigner: false,
+          isWritable: account.is_writable,
+        })),
       ],
       data: decodeBase64(intent.instruction_data),
     });
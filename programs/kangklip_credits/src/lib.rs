@@ -1,22 +1,195 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use solana_address_lookup_table_interface::{
+    instruction as alt_instruction, program as alt_program,
+};
 
 declare_id!("KngKLPcRedit1111111111111111111111111111");
 
 const CREDIT_UNIT: u64 = 100_000;
+// Bump on every deploy that needs a migration; confirm_upgrade re-arms each config.
+const PROGRAM_VERSION: u32 = 1;
+const MIN_CREDIT_UNIT: u64 = 1_000;
+const MAX_CREDIT_UNIT: u64 = 100_000_000;
+const BPS_DENOMINATOR: u64 = 10_000;
+const MAX_CPI_CALLERS: usize = 4;
+const MAX_REASON_LEN: usize = 64;
+const MAX_LABEL_LEN: usize = 32;
+const RESERVED_LEN: usize = 64;
+const LAMPORTS_PER_SOL: u128 = 1_000_000_000;
+const USD_BASE_UNITS: u128 = 1_000_000;
+const USD_DECIMALS: u8 = 6;
+const JOURNAL_CAPACITY: usize = 64;
+const MAX_PRICE_TIERS: usize = 4;
+const MAX_PRICING_VARIANTS: usize = 4;
+const MAX_HOLDER_TIERS: usize = 4;
+const MAX_LOOKUP_TABLE_EXTEND: usize = 20;
+const MAX_VOUCHER_CODE_LEN: usize = 32;
+const MIN_CARD_SERIAL_LEN: usize = 16;
+const MAX_CARD_SERIAL_LEN: usize = 32;
+const QUOTE_DOMAIN: &[u8] = b"kangklip-quote";
+const PERMIT_DOMAIN: &[u8] = b"kangklip-permit";
+const MAX_MERKLE_PROOF_LEN: usize = 32;
+const MAX_MEMO_LEN: usize = 64;
+const SUNSET_DELAY_SECS: i64 = 7 * 86_400;
+const MAX_PAYMENT_REFERENCES: usize = 4;
+const MAX_ORG_MEMBERS: usize = 16;
+const MAX_CURVE_STEPS_PER_PURCHASE: usize = 64;
+const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+const METADATA_V1_KEY: u8 = 4;
+const JUPITER_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+#[cfg(feature = "devnet-faucet")]
+const FAUCET_CREDITS_PER_DAY: u64 = 10;
+#[cfg(feature = "devnet-faucet")]
+const SECONDS_PER_DAY: i64 = 86_400;
 
 #[program]
 pub mod kangklip_credits {
     use super::*;
 
+    // Create the singleton registry counter; each config gets its own entry PDA.
+    pub fn initialize_registry(ctx: Context<InitializeRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.config_count = 0;
+        registry.bump = ctx.bumps.registry;
+        Ok(())
+    }
+
+    // Create the global state PDA; only the program upgrade authority may do this.
+    pub fn initialize_global(ctx: Context<InitializeGlobal>) -> Result<()> {
+        let global = &mut ctx.accounts.global;
+        global.super_authority = ctx.accounts.super_authority.key();
+        global.frozen = false;
+        global.bump = ctx.bumps.global;
+        Ok(())
+    }
+
+    // Route `fee_bps` of a partner config's purchases to the platform config's vault for
+    // the paying mint.
+    pub fn set_partner_fee(ctx: Context<SetPartnerFee>, fee_bps: u16) -> Result<()> {
+        require!(
+            u64::from(fee_bps) <= BPS_DENOMINATOR,
+            CreditsError::InvalidAmount
+        );
+        let partner_config = &mut ctx.accounts.partner_config;
+        require!(
+            partner_config.key() != ctx.accounts.platform_config.key(),
+            CreditsError::InvalidPlatformVault
+        );
+        partner_config.platform_fee_bps = fee_bps;
+        partner_config.platform_config = ctx.accounts.platform_config.key();
+        sync_price_feed(&mut ctx.accounts.partner_price_feed, partner_config)?;
+
+        emit!(PartnerFeeSet {
+            partner_config: partner_config.key(),
+            platform_config: partner_config.platform_config,
+            fee_bps,
+        });
+        Ok(())
+    }
+
+    // Halt (or resume) purchases, withdrawals and debits across every config.
+    pub fn set_global_freeze(ctx: Context<SetGlobalFreeze>, frozen: bool) -> Result<()> {
+        let global = &mut ctx.accounts.global;
+        global.frozen = frozen;
+        emit!(GlobalFreezeSet {
+            super_authority: ctx.accounts.super_authority.key(),
+            frozen,
+        });
+        Ok(())
+    }
+
     // Initialize config PDA with authority and USDC mint.
     pub fn initialize_config(ctx: Context<InitializeConfig>, usdc_mint: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let entry = &mut ctx.accounts.registry_entry;
+        entry.index = registry.config_count;
+        entry.config = ctx.accounts.config.key();
+        entry.authority = ctx.accounts.authority.key();
+        entry.usdc_mint = usdc_mint;
+        entry.created_at = Clock::get()?.unix_timestamp;
+        entry.bump = ctx.bumps.registry_entry;
+        registry.config_count = registry
+            .config_count
+            .checked_add(1)
+            .ok_or(CreditsError::Overflow)?;
+
         let config = &mut ctx.accounts.config;
         config.authority = ctx.accounts.authority.key();
         config.spender = ctx.accounts.authority.key();
         config.usdc_mint = usdc_mint;
         config.credit_unit = CREDIT_UNIT;
         config.bump = ctx.bumps.config;
+        config.spender_last_active_slot = Clock::get()?.slot;
+        config.spender_inactivity_slots = 0;
+        config.program_version = PROGRAM_VERSION;
+        Ok(())
+    }
+
+    // Create the price feed PDA that mirrors this config's effective pricing.
+    pub fn initialize_price_feed(ctx: Context<InitializePriceFeed>) -> Result<()> {
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.config = ctx.accounts.config.key();
+        price_feed.bump = ctx.bumps.price_feed;
+        sync_price_feed(price_feed, &ctx.accounts.config)
+    }
+
+    // Permissionless: republish the config's current pricing into the price feed.
+    pub fn publish_price(ctx: Context<PublishPrice>) -> Result<()> {
+        sync_price_feed(&mut ctx.accounts.price_feed, &ctx.accounts.config)
+    }
+
+    // Acknowledge that this config has been migrated for the deployed program version.
+    pub fn confirm_upgrade(ctx: Context<UpdateConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.program_version != PROGRAM_VERSION,
+            CreditsError::AlreadyCurrentVersion
+        );
+        let old_value = u64::from(config.program_version);
+        config.program_version = PROGRAM_VERSION;
+
+        emit!(ConfigUpdated {
+            config: config.key(),
+            field: ConfigField::ProgramVersion,
+            old_value,
+            new_value: u64::from(PROGRAM_VERSION),
+        });
+        Ok(())
+    }
+
+    // Reprice credits and refresh the price feed.
+    pub fn set_credit_unit(ctx: Context<UpdatePricing>, credit_unit: u64) -> Result<()> {
+        require!(
+            (MIN_CREDIT_UNIT..=MAX_CREDIT_UNIT).contains(&credit_unit),
+            CreditsError::InvalidCreditUnit
+        );
+        let config = &mut ctx.accounts.config;
+        let old_value = config.credit_unit;
+        config.credit_unit = credit_unit;
+        sync_price_feed(&mut ctx.accounts.price_feed, config)?;
+
+        emit!(ConfigUpdated {
+            config: config.key(),
+            field: ConfigField::CreditUnit,
+            old_value,
+            new_value: credit_unit,
+        });
         Ok(())
     }
 
@@ -24,252 +197,7474 @@ pub mod kangklip_credits {
     pub fn set_spender(ctx: Context<SetSpender>, spender: Pubkey) -> Result<()> {
         let config = &mut ctx.accounts.config;
         config.spender = spender;
+        config.spender_last_active_slot = Clock::get()?.slot;
         Ok(())
     }
 
-    // Accept a user USDC transfer and mint credits.
-    pub fn pay_usdc(ctx: Context<PayUsdc>, amount_base_units: u64) -> Result<()> {
-        require!(amount_base_units > 0, CreditsError::InvalidAmount);
-        let config = &ctx.accounts.config;
+    // Configure how many slots the spender may stay idle before it can be suspended (0 disables).
+    pub fn set_spender_inactivity(ctx: Context<UpdateConfig>, inactivity_slots: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.spender_inactivity_slots = inactivity_slots;
+        Ok(())
+    }
+
+    // Permissionless: disable a spender that has been idle beyond the configured horizon.
+    pub fn suspend_stale_spender(ctx: Context<SuspendStaleSpender>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
         require!(
-            ctx.accounts.usdc_mint.key() == config.usdc_mint,
-            CreditsError::InvalidMint
+            config.spender_inactivity_slots > 0,
+            CreditsError::InactivityDisabled
         );
         require!(
-            ctx.accounts.user_usdc.mint == config.usdc_mint,
-            CreditsError::InvalidMint
+            config.spender != Pubkey::default(),
+            CreditsError::SpenderNotStale
         );
+        let slot = Clock::get()?.slot;
+        let idle_slots = slot.saturating_sub(config.spender_last_active_slot);
         require!(
-            ctx.accounts.vault_usdc.mint == config.usdc_mint,
-            CreditsError::InvalidMint
+            idle_slots > config.spender_inactivity_slots,
+            CreditsError::SpenderNotStale
+        );
+
+        let spender = config.spender;
+        config.spender = Pubkey::default();
+        emit!(SpenderSuspended {
+            spender,
+            last_active_slot: config.spender_last_active_slot,
+            slot,
+        });
+        Ok(())
+    }
+
+    // Require a second spender signature on debits of at least `threshold` credits (0 disables).
+    pub fn set_co_spender(
+        ctx: Context<UpdateConfig>,
+        co_spender: Pubkey,
+        threshold: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            threshold == 0 || co_spender != config.spender,
+            CreditsError::InvalidCoSpender
         );
+        config.co_spender = co_spender;
+        config.co_sign_threshold = threshold;
+        Ok(())
+    }
+
+    // Set the guardian key that co-signs balance corrections.
+    pub fn set_guardian(ctx: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.guardian = guardian;
+        Ok(())
+    }
+
+    // Authority + guardian adjustment of a user's balance after an incident.
+    pub fn post_correction(
+        ctx: Context<PostCorrection>,
+        signed_delta: i64,
+        reason: String,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(signed_delta != 0, CreditsError::InvalidAmount);
+        require!(reason.len() <= MAX_REASON_LEN, CreditsError::ReasonTooLong);
+        let user_credit = &mut ctx.accounts.user_credit;
         require!(
-            ctx.accounts.user_usdc.owner == ctx.accounts.user.key(),
+            user_credit.user == ctx.accounts.user.key(),
             CreditsError::InvalidOwner
         );
+        let magnitude = signed_delta.unsigned_abs();
+        user_credit.credits = if signed_delta > 0 {
+            user_credit
+                .credits
+                .checked_add(magnitude)
+                .ok_or(CreditsError::Overflow)?
+        } else {
+            user_credit
+                .credits
+                .checked_sub(magnitude)
+                .ok_or(CreditsError::InsufficientCredits)?
+        };
+
+        let config = &mut ctx.accounts.config;
+        if signed_delta > 0 {
+            config.total_credits_issued = config
+                .total_credits_issued
+                .checked_add(magnitude)
+                .ok_or(CreditsError::Overflow)?;
+        } else {
+            config.total_credits_consumed = config
+                .total_credits_consumed
+                .checked_add(magnitude)
+                .ok_or(CreditsError::Overflow)?;
+        }
+
+        let kind = if signed_delta > 0 {
+            JournalKind::CorrectionCredit
+        } else {
+            JournalKind::CorrectionDebit
+        };
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            kind,
+            ctx.accounts.user.key(),
+            magnitude,
+        )?;
+
+        emit!(Correction {
+            config: ctx.accounts.config.key(),
+            user: ctx.accounts.user.key(),
+            signed_delta,
+            new_balance: user_credit.credits,
+            reason,
+            evidence_hash,
+            authority: ctx.accounts.authority.key(),
+            guardian: ctx.accounts.guardian.key(),
+        });
+        let category = if signed_delta > 0 {
+            BalanceCategory::CorrectionCredit
+        } else {
+            BalanceCategory::CorrectionDebit
+        };
+        emit_balance_changed(
+            ctx.accounts.user.key(),
+            category,
+            magnitude,
+            user_credit.credits,
+        )?;
+        Ok(())
+    }
+
+    // List an off-chain read service that may query user data for this config.
+    pub fn register_viewer(ctx: Context<RegisterViewer>, key: Pubkey, label: String) -> Result<()> {
+        require!(label.len() <= MAX_LABEL_LEN, CreditsError::LabelTooLong);
+        let viewer = &mut ctx.accounts.viewer;
+        viewer.config = ctx.accounts.config.key();
+        viewer.key = key;
+        viewer.label = label;
+        viewer.registered_at = Clock::get()?.unix_timestamp;
+        viewer.bump = ctx.bumps.viewer;
+
+        emit!(ViewerRegistered {
+            config: viewer.config,
+            key,
+            label: viewer.label.clone(),
+        });
+        Ok(())
+    }
+
+    // Delist a read service and reclaim its rent.
+    pub fn remove_viewer(ctx: Context<RemoveViewer>) -> Result<()> {
+        emit!(ViewerRemoved {
+            config: ctx.accounts.config.key(),
+            key: ctx.accounts.viewer.key,
+        });
+        Ok(())
+    }
+
+    // Record an authorized webhook endpoint (hashed) and its HMAC key fingerprint.
+    pub fn register_webhook(
+        ctx: Context<RegisterWebhook>,
+        endpoint_hash: [u8; 32],
+        key_fingerprint: [u8; 32],
+    ) -> Result<()> {
+        let webhook = &mut ctx.accounts.webhook;
+        webhook.config = ctx.accounts.config.key();
+        webhook.endpoint_hash = endpoint_hash;
+        webhook.key_fingerprint = key_fingerprint;
+        webhook.registered_at = Clock::get()?.unix_timestamp;
+        webhook.revoked_at = 0;
+        webhook.bump = ctx.bumps.webhook;
+
+        emit!(WebhookRegistered {
+            config: webhook.config,
+            endpoint_hash,
+            key_fingerprint,
+            registered_at: webhook.registered_at,
+        });
+        Ok(())
+    }
+
+    // Mark a webhook endpoint revoked; the record is kept for audit.
+    pub fn revoke_webhook(ctx: Context<RevokeWebhook>) -> Result<()> {
+        let webhook = &mut ctx.accounts.webhook;
+        require!(webhook.revoked_at == 0, CreditsError::WebhookAlreadyRevoked);
+        webhook.revoked_at = Clock::get()?.unix_timestamp;
+
+        emit!(WebhookRevoked {
+            config: webhook.config,
+            endpoint_hash: webhook.endpoint_hash,
+            revoked_at: webhook.revoked_at,
+        });
+        Ok(())
+    }
+
+    // Restrict which programs may invoke user-signed instructions via CPI.
+    pub fn set_cpi_guard(
+        ctx: Context<UpdateConfig>,
+        enabled: bool,
+        allowed_callers: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(
+            allowed_callers.len() <= MAX_CPI_CALLERS,
+            CreditsError::TooManyCpiCallers
+        );
+        let config = &mut ctx.accounts.config;
+        config.cpi_guard = enabled;
+        config.cpi_allowed_callers = [Pubkey::default(); MAX_CPI_CALLERS];
+        config.cpi_allowed_callers[..allowed_callers.len()].copy_from_slice(&allowed_callers);
+        Ok(())
+    }
+
+    // Configure the USDC/USD depeg circuit breaker (band_bps = 0 disables it).
+    pub fn set_depeg_guard(
+        ctx: Context<UpdateConfig>,
+        feed_id: [u8; 32],
+        band_bps: u16,
+        max_age_secs: u64,
+    ) -> Result<()> {
+        require!(
+            u64::from(band_bps) <= BPS_DENOMINATOR,
+            CreditsError::InvalidAmount
+        );
+        let config = &mut ctx.accounts.config;
+        config.usdc_feed_id = feed_id;
+        config.depeg_band_bps = band_bps;
+        config.depeg_max_age_secs = max_age_secs;
+        Ok(())
+    }
+
+    // Permissionless: trip the depeg breaker when the USDC price leaves the band.
+    pub fn check_depeg(ctx: Context<CheckDepeg>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.depeg_band_bps > 0, CreditsError::DepegGuardDisabled);
+        let deviation_bps = usdc_deviation_bps(config, &ctx.accounts.price_update)?;
+        require!(
+            deviation_bps > u64::from(config.depeg_band_bps),
+            CreditsError::PriceWithinBand
+        );
+
+        config.depeg_tripped = true;
+        emit!(DepegTripped {
+            config: config.key(),
+            deviation_bps,
+        });
+        Ok(())
+    }
+
+    // Clear a tripped depeg breaker once the incident is over.
+    pub fn reset_depeg(ctx: Context<UpdateConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.depeg_tripped = false;
+        Ok(())
+    }
+
+    // Configure the dormancy guard (dormancy_secs = 0 disables it).
+    pub fn set_dormancy_guard(
+        ctx: Context<UpdateConfig>,
+        dormancy_secs: i64,
+        confirm_delay_secs: i64,
+    ) -> Result<()> {
+        require!(
+            dormancy_secs >= 0 && confirm_delay_secs >= 0,
+            CreditsError::InvalidAmount
+        );
+        let config = &mut ctx.accounts.config;
+        config.dormancy_secs = dormancy_secs;
+        config.pending_confirm_delay_secs = confirm_delay_secs;
+        Ok(())
+    }
+
+    // Route every spender debit through a per-job authorization instead of consume_credit.
+    pub fn set_job_authorization_required(
+        ctx: Context<UpdateConfig>,
+        required: bool,
+    ) -> Result<()> {
+        ctx.accounts.config.job_authorization_required = required;
+        Ok(())
+    }
+
+    // Schedule the config's irreversible read-only state after the sunset delay.
+    pub fn schedule_sunset(ctx: Context<UpdateConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.sunset_at == 0, CreditsError::SunsetAlreadyScheduled);
+        config.sunset_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(SUNSET_DELAY_SECS)
+            .ok_or(CreditsError::Overflow)?;
+
+        emit!(SunsetScheduled {
+            config: config.key(),
+            sunset_at: config.sunset_at,
+        });
+        Ok(())
+    }
+
+    // Cancel a scheduled sunset while it is still inside its delay window.
+    pub fn cancel_sunset(ctx: Context<UpdateConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.sunset_at != 0, CreditsError::SunsetNotScheduled);
+        require_not_sunset(config)?;
+        let cancelled_sunset_at = config.sunset_at;
+        config.sunset_at = 0;
+
+        emit!(SunsetCancelled {
+            config: config.key(),
+            cancelled_sunset_at,
+        });
+        Ok(())
+    }
+
+    // Register a segregated regional vault; deposits must then name their region.
+    pub fn add_region_vault(ctx: Context<AddRegionVault>, region: [u8; 4]) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let vault_token = &ctx.accounts.vault_token;
+        require!(
+            vault_token.mint == ctx.accounts.accepted_mint.mint,
+            CreditsError::InvalidMint
+        );
         require!(
-            ctx.accounts.vault_usdc.owner == ctx.accounts.config.key(),
+            vault_token.owner == config.key(),
             CreditsError::InvalidOwner
         );
+        config.region_count = config
+            .region_count
+            .checked_add(1)
+            .ok_or(CreditsError::Overflow)?;
 
-        let credits_to_add = amount_base_units / config.credit_unit;
-        require!(credits_to_add > 0, CreditsError::BelowMinimum);
+        let region_vault = &mut ctx.accounts.region_vault;
+        region_vault.config = config.key();
+        region_vault.region = region;
+        region_vault.vault_token = vault_token.key();
+        region_vault.mint = vault_token.mint;
+        region_vault.decimals = ctx.accounts.accepted_mint.decimals;
+        region_vault.bump = ctx.bumps.region_vault;
+
+        emit!(RegionVaultAdded {
+            config: region_vault.config,
+            region,
+            vault_token: region_vault.vault_token,
+        });
+        Ok(())
+    }
+
+    // Deregister a regional vault once it has been drained.
+    pub fn remove_region_vault(ctx: Context<RemoveRegionVault>) -> Result<()> {
+        require!(
+            ctx.accounts.vault_token.amount == 0,
+            CreditsError::RegionVaultNotEmpty
+        );
+        let config = &mut ctx.accounts.config;
+        config.region_count = config
+            .region_count
+            .checked_sub(1)
+            .ok_or(CreditsError::Overflow)?;
+
+        emit!(RegionVaultRemoved {
+            config: config.key(),
+            region: ctx.accounts.region_vault.region,
+        });
+        Ok(())
+    }
+
+    // Register a stablecoin mint accepted by pay_stable, creating its config-owned vault ATA.
+    pub fn add_accepted_mint(ctx: Context<AddAcceptedMint>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.accepted_mint_count = config
+            .accepted_mint_count
+            .checked_add(1)
+            .ok_or(CreditsError::Overflow)?;
+        let accepted_mint = &mut ctx.accounts.accepted_mint;
+        accepted_mint.config = ctx.accounts.config.key();
+        accepted_mint.mint = ctx.accounts.mint.key();
+        accepted_mint.decimals = ctx.accounts.mint.decimals;
+        accepted_mint.vault = ctx.accounts.vault_token.key();
+        accepted_mint.bump = ctx.bumps.accepted_mint;
+
+        emit!(AcceptedMintAdded {
+            config: accepted_mint.config,
+            mint: accepted_mint.mint,
+            decimals: accepted_mint.decimals,
+            vault: accepted_mint.vault,
+        });
+        Ok(())
+    }
+
+    // Stop accepting a drained stablecoin mint and reclaim its registry rent.
+    pub fn remove_accepted_mint(ctx: Context<RemoveAcceptedMint>) -> Result<()> {
+        require!(
+            ctx.accounts.vault.amount == 0,
+            CreditsError::AcceptedMintVaultNotEmpty
+        );
+        let config = &mut ctx.accounts.config;
+        config.accepted_mint_count = config
+            .accepted_mint_count
+            .checked_sub(1)
+            .ok_or(CreditsError::Overflow)?;
+        emit!(AcceptedMintRemoved {
+            config: ctx.accounts.config.key(),
+            mint: ctx.accounts.accepted_mint.mint,
+        });
+        Ok(())
+    }
+
+    // Accept a user stablecoin transfer in any registered mint and mint credits.
+    pub fn pay_stable(
+        ctx: Context<PayStable>,
+        amount_base_units: u64,
+        voucher_code: Option<String>,
+    ) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        require!(amount_base_units > 0, CreditsError::InvalidAmount);
+        let config = &ctx.accounts.config;
+        let accepted_mint = &ctx.accounts.accepted_mint;
+        require_stable_accounts(
+            config,
+            accepted_mint,
+            &ctx.accounts.mint,
+            &ctx.accounts.user_token,
+            &ctx.accounts.vault_token,
+            ctx.accounts.user.key(),
+            ctx.accounts.region_vault.as_ref(),
+        )?;
+        require_payment_guards(
+            config,
+            ctx.accounts.price_update.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+
+        require_purchase_limits(
+            config,
+            to_usd_base_units(amount_base_units, accepted_mint.decimals)?,
+        )?;
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.user_usdc.to_account_info(),
-            to: ctx.accounts.vault_usdc.to_account_info(),
+        // Charge whole credits only, so no remainder is left behind in the vault.
+        let amount_base_units =
+            whole_credit_charge(config, amount_base_units, accepted_mint.decimals)?;
+
+        // Credits are priced on what actually lands in program-controlled
+        // accounts, so Token-2022 transfer fees are borne by the payer.
+        let payer = StablePayer {
+            token_program: &ctx.accounts.token_program,
+            mint: &ctx.accounts.mint,
+            from: &ctx.accounts.user_token,
             authority: ctx.accounts.user.to_account_info(),
+            signer_seeds: &[],
         };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount_base_units)?;
+        let received_base_units = receive_stable_payment(
+            config,
+            &payer,
+            ctx.accounts.platform_vault.as_mut(),
+            &mut ctx.accounts.vault_token,
+            amount_base_units,
+        )?;
 
-        let user_credit = &mut ctx.accounts.user_credit;
-        user_credit.user = ctx.accounts.user.key();
-        user_credit.credits = user_credit
-            .credits
-            .checked_add(credits_to_add)
+        let usd_base_units = to_usd_base_units(received_base_units, accepted_mint.decimals)?;
+        let purchase = price_purchase(
+            config,
+            usd_base_units,
+            ctx.accounts.user_credit.remainder_base_units,
+            &PurchaseOffers {
+                user: ctx.accounts.user.key(),
+                price_tiers: ctx.accounts.price_tiers.as_ref(),
+                voucher: ctx.accounts.voucher.as_ref(),
+                voucher_code: voucher_code.as_deref(),
+                campaign: ctx.accounts.campaign.as_ref(),
+                membership_token: ctx.accounts.membership_token.as_ref(),
+                membership_metadata: ctx.accounts.membership_metadata.as_ref(),
+                holder_tiers: ctx.accounts.holder_tiers.as_ref(),
+                holder_token: ctx.accounts.holder_token.as_ref(),
+            },
+        )?;
+        if let Some(voucher) = ctx.accounts.voucher.as_mut() {
+            voucher.uses += 1;
+            emit!(VoucherRedeemed {
+                config: voucher.config,
+                user: ctx.accounts.user.key(),
+                code_hash: voucher.code_hash,
+                bonus_credits: purchase.voucher_bonus,
+                uses: voucher.uses,
+            });
+        }
+        let base_credits = purchase.base_credits;
+        let bonus_credits = purchase.bonus_credits;
+        let credits_to_add = base_credits
+            .checked_add(bonus_credits)
             .ok_or(CreditsError::Overflow)?;
-        user_credit.bump = ctx.bumps.user_credit;
+
+        ctx.accounts.user_credit.remainder_base_units = purchase.remainder_base_units;
+        ctx.accounts.user_credit.last_active_at = Clock::get()?.unix_timestamp;
+        ctx.accounts.user_credit.bump = ctx.bumps.user_credit;
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            ctx.accounts.price_feed.as_mut(),
+            &mut ctx.accounts.user_credit,
+            CreditedPurchase {
+                kind: JournalKind::Paid,
+                user: ctx.accounts.user.key(),
+                credits: credits_to_add,
+                sold_credits: base_credits,
+            },
+        )?;
+        let user_credit = &ctx.accounts.user_credit;
 
         emit!(Paid {
             user: ctx.accounts.user.key(),
+            beneficiary: ctx.accounts.user.key(),
+            references: payment_references(ctx.remaining_accounts)?,
             amount_base_units,
             credits_added: credits_to_add,
             new_balance: user_credit.credits,
+            mint: ctx.accounts.mint.key(),
+            received_base_units,
+            bonus_credits,
+            variant_id: purchase.variant_id,
         });
+
+        if let Some(referrer_credit) = ctx.accounts.referrer_credit.as_mut() {
+            require!(
+                referrer_credit.user != ctx.accounts.user.key(),
+                CreditsError::SelfReferral
+            );
+            let referral_credits = base_credits
+                .checked_mul(u64::from(ctx.accounts.config.referral_bps))
+                .ok_or(CreditsError::Overflow)?
+                / BPS_DENOMINATOR;
+            if referral_credits > 0 {
+                referrer_credit.credits = referrer_credit
+                    .credits
+                    .checked_add(referral_credits)
+                    .ok_or(CreditsError::Overflow)?;
+                let config = &mut ctx.accounts.config;
+                config.total_credits_issued = config
+                    .total_credits_issued
+                    .checked_add(referral_credits)
+                    .ok_or(CreditsError::Overflow)?;
+
+                record_journal(
+                    &ctx.accounts.config,
+                    ctx.accounts.journal.as_mut(),
+                    JournalKind::Referral,
+                    referrer_credit.user,
+                    referral_credits,
+                )?;
+
+                emit!(ReferralPaid {
+                    referrer: referrer_credit.user,
+                    user: ctx.accounts.user.key(),
+                    credits: referral_credits,
+                    new_balance: referrer_credit.credits,
+                });
+                emit_balance_changed(
+                    referrer_credit.user,
+                    BalanceCategory::Referral,
+                    referral_credits,
+                    referrer_credit.credits,
+                )?;
+            }
+        }
         Ok(())
     }
 
-    // Allow the authority to withdraw USDC from the vault.
-    pub fn withdraw_usdc(ctx: Context<WithdrawUsdc>, amount_base_units: u64) -> Result<()> {
+    // Read-only: price a hypothetical pay_stable purchase, returned via return data.
+    // Takes the same optional offer accounts as pay_stable and prices through price_purchase.
+    pub fn quote_credits(
+        ctx: Context<QuoteCredits>,
+        amount_base_units: u64,
+        voucher_code: Option<String>,
+    ) -> Result<CreditQuote> {
+        let config = &ctx.accounts.config;
+        let decimals = ctx.accounts.accepted_mint.decimals;
+        require_purchase_limits(config, to_usd_base_units(amount_base_units, decimals)?)?;
+        let charged_base_units = whole_credit_charge(config, amount_base_units, decimals)?;
+
+        // Mirror pay_stable's two transfers, each of which may lose a Token-2022 fee.
+        let mint = &ctx.accounts.mint;
+        let platform_fee = platform_fee_for(config, charged_base_units)?;
+        let withheld = transfer_fee(mint, platform_fee)?
+            .checked_add(transfer_fee(mint, charged_base_units - platform_fee)?)
+            .ok_or(CreditsError::Overflow)?;
+        let received_base_units = charged_base_units.saturating_sub(withheld);
+
+        let purchase = price_purchase(
+            config,
+            to_usd_base_units(received_base_units, decimals)?,
+            ctx.accounts
+                .user_credit
+                .as_ref()
+                .map_or(0, |user_credit| user_credit.remainder_base_units),
+            &PurchaseOffers {
+                user: ctx.accounts.user.key(),
+                price_tiers: ctx.accounts.price_tiers.as_ref(),
+                voucher: ctx.accounts.voucher.as_ref(),
+                voucher_code: voucher_code.as_deref(),
+                campaign: ctx.accounts.campaign.as_ref(),
+                membership_token: ctx.accounts.membership_token.as_ref(),
+                membership_metadata: ctx.accounts.membership_metadata.as_ref(),
+                holder_tiers: ctx.accounts.holder_tiers.as_ref(),
+                holder_token: ctx.accounts.holder_token.as_ref(),
+            },
+        )?;
+        Ok(CreditQuote {
+            charged_base_units,
+            dust_base_units: amount_base_units - charged_base_units,
+            base_credits: purchase.base_credits,
+            bonus_credits: purchase.bonus_credits,
+            total_credits: purchase
+                .base_credits
+                .checked_add(purchase.bonus_credits)
+                .ok_or(CreditsError::Overflow)?,
+        })
+    }
+
+    // Sponsored top-up: the payer's stablecoin funds another wallet's credits.
+    pub fn pay_for(ctx: Context<PayFor>, amount_base_units: u64) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
         require!(amount_base_units > 0, CreditsError::InvalidAmount);
         let config = &ctx.accounts.config;
+        let accepted_mint = &ctx.accounts.accepted_mint;
+        require_stable_accounts(
+            config,
+            accepted_mint,
+            &ctx.accounts.mint,
+            &ctx.accounts.payer_token,
+            &ctx.accounts.vault_token,
+            ctx.accounts.payer.key(),
+            ctx.accounts.region_vault.as_ref(),
+        )?;
+        require_payment_guards(
+            config,
+            ctx.accounts.price_update.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+
+        require_purchase_limits(
+            config,
+            to_usd_base_units(amount_base_units, accepted_mint.decimals)?,
+        )?;
+
+        // Charge whole credits only, so no remainder is left behind in the vault.
+        let amount_base_units =
+            whole_credit_charge(config, amount_base_units, accepted_mint.decimals)?;
+
+        let payer = StablePayer {
+            token_program: &ctx.accounts.token_program,
+            mint: &ctx.accounts.mint,
+            from: &ctx.accounts.payer_token,
+            authority: ctx.accounts.payer.to_account_info(),
+            signer_seeds: &[],
+        };
+        let received_base_units = receive_stable_payment(
+            config,
+            &payer,
+            ctx.accounts.platform_vault.as_mut(),
+            &mut ctx.accounts.vault_token,
+            amount_base_units,
+        )?;
+
+        // Variants and the bonus cap follow the credited wallet, as in pay_stable.
+        let usd_base_units = to_usd_base_units(received_base_units, accepted_mint.decimals)?;
+        let beneficiary = ctx.accounts.beneficiary.key();
+        let purchase = price_purchase(
+            config,
+            usd_base_units,
+            ctx.accounts.beneficiary_credit.remainder_base_units,
+            &PurchaseOffers {
+                user: beneficiary,
+                price_tiers: ctx.accounts.price_tiers.as_ref(),
+                voucher: None,
+                voucher_code: None,
+                campaign: None,
+                membership_token: None,
+                membership_metadata: None,
+                holder_tiers: None,
+                holder_token: None,
+            },
+        )?;
+        let bonus_credits = purchase.bonus_credits;
+        let credits_to_add = purchase
+            .base_credits
+            .checked_add(bonus_credits)
+            .ok_or(CreditsError::Overflow)?;
+
+        ctx.accounts.beneficiary_credit.remainder_base_units = purchase.remainder_base_units;
+        ctx.accounts.beneficiary_credit.bump = ctx.bumps.beneficiary_credit;
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            ctx.accounts.price_feed.as_mut(),
+            &mut ctx.accounts.beneficiary_credit,
+            CreditedPurchase {
+                kind: JournalKind::Paid,
+                user: beneficiary,
+                credits: credits_to_add,
+                sold_credits: purchase.base_credits,
+            },
+        )?;
+        let beneficiary_credit = &ctx.accounts.beneficiary_credit;
+
+        emit!(Paid {
+            user: ctx.accounts.payer.key(),
+            beneficiary,
+            references: payment_references(ctx.remaining_accounts)?,
+            amount_base_units,
+            credits_added: credits_to_add,
+            new_balance: beneficiary_credit.credits,
+            mint: ctx.accounts.mint.key(),
+            received_base_units,
+            bonus_credits,
+            variant_id: purchase.variant_id,
+        });
+        Ok(())
+    }
+
+    // Swap any token into the vault through a Jupiter route and credit the measured output.
+    // The route (data + remaining accounts) is built off-chain; only the vault delta counts.
+    pub fn pay_any_token<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PayAnyToken<'info>>,
+        route_data: Vec<u8>,
+        min_out_base_units: u64,
+    ) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        require!(min_out_base_units > 0, CreditsError::InvalidAmount);
+        let config = &ctx.accounts.config;
+        let accepted_mint = &ctx.accounts.accepted_mint;
+        let vault_token = &ctx.accounts.vault_token;
         require!(
-            ctx.accounts.treasury_usdc.mint == config.usdc_mint,
+            vault_token.mint == accepted_mint.mint,
             CreditsError::InvalidMint
         );
         require!(
-            ctx.accounts.vault_usdc.mint == config.usdc_mint,
-            CreditsError::InvalidMint
+            vault_token.owner == config.key(),
+            CreditsError::InvalidOwner
         );
+        if config.region_count > 0 {
+            let region_vault = ctx
+                .accounts
+                .region_vault
+                .as_ref()
+                .ok_or(CreditsError::MissingRegionVault)?;
+            require!(
+                region_vault.vault_token == vault_token.key(),
+                CreditsError::InvalidRegionVault
+            );
+        } else {
+            require!(
+                vault_token.key() == accepted_mint.vault,
+                CreditsError::InvalidVault
+            );
+        }
+        require_payment_guards(
+            config,
+            ctx.accounts.price_update.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+
+        // The config PDA never signs the route, so it can only add to the vault.
+        let balance_before = vault_token.amount;
+        let route = Instruction {
+            program_id: JUPITER_PROGRAM_ID,
+            accounts: ctx
+                .remaining_accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.key(),
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: route_data,
+        };
+        let mut route_accounts = ctx.remaining_accounts.to_vec();
+        route_accounts.push(ctx.accounts.jupiter_program.to_account_info());
+        invoke(&route, &route_accounts)?;
+        ctx.accounts.vault_token.reload()?;
+        let received_base_units = ctx
+            .accounts
+            .vault_token
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(CreditsError::Overflow)?;
         require!(
-            ctx.accounts.vault_usdc.owner == config.key(),
-            CreditsError::InvalidOwner
+            received_base_units >= min_out_base_units,
+            CreditsError::SlippageExceeded
         );
 
-        let seeds = &[b"config", config.authority.as_ref(), &[config.bump]];
+        let config = &ctx.accounts.config;
+        let decimals = ctx.accounts.accepted_mint.decimals;
+        let usd_base_units = to_usd_base_units(received_base_units, decimals)?;
+        require_purchase_limits(config, usd_base_units)?;
+        let credits_to_add =
+            credits_with_remainder(config, &mut ctx.accounts.user_credit, usd_base_units)?;
+        require!(credits_to_add > 0, CreditsError::BelowMinimum);
+
+        // The partner share is carved out of the swap output already in the vault.
+        let authority = config.authority;
+        let seeds = &[b"config", authority.as_ref(), &[config.bump]];
         let signer = &[&seeds[..]];
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.vault_usdc.to_account_info(),
-            to: ctx.accounts.treasury_usdc.to_account_info(),
+        let vault = StablePayer {
+            token_program: &ctx.accounts.token_program,
+            mint: &ctx.accounts.mint,
+            from: &ctx.accounts.vault_token,
             authority: ctx.accounts.config.to_account_info(),
+            signer_seeds: signer,
         };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        token::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer), amount_base_units)?;
+        collect_platform_fee(
+            &ctx.accounts.config,
+            &vault,
+            ctx.accounts.platform_vault.as_mut(),
+            received_base_units,
+        )?;
 
-        emit!(Withdrawn {
-            authority: ctx.accounts.authority.key(),
-            amount_base_units,
+        let user = ctx.accounts.user.key();
+        ctx.accounts.user_credit.last_active_at = Clock::get()?.unix_timestamp;
+        ctx.accounts.user_credit.bump = ctx.bumps.user_credit;
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            ctx.accounts.price_feed.as_mut(),
+            &mut ctx.accounts.user_credit,
+            CreditedPurchase {
+                kind: JournalKind::Paid,
+                user,
+                credits: credits_to_add,
+                sold_credits: credits_to_add,
+            },
+        )?;
+        let user_credit = &ctx.accounts.user_credit;
+
+        emit!(Paid {
+            user,
+            beneficiary: user,
+            references: Vec::new(),
+            amount_base_units: received_base_units,
+            credits_added: credits_to_add,
+            new_balance: user_credit.credits,
+            mint: ctx.accounts.mint.key(),
+            received_base_units,
+            bonus_credits: 0,
+            variant_id: None,
         });
         Ok(())
     }
 
-    // Admin/spender-only debit of user credits.
-    pub fn consume_credit(ctx: Context<ConsumeCredit>, amount: u64) -> Result<()> {
-        require!(amount > 0, CreditsError::InvalidAmount);
-        let config = &ctx.accounts.config;
+    // User subscribes to periodic top-ups pulled via a delegation to the config PDA.
+    pub fn create_subscription(
+        ctx: Context<CreateSubscription>,
+        plan_id: u32,
+        period_secs: i64,
+        amount_base_units: u64,
+    ) -> Result<()> {
         require!(
-            ctx.accounts.spender.key() == config.spender,
-            CreditsError::Unauthorized
+            period_secs > 0 && amount_base_units > 0,
+            CreditsError::InvalidAmount
         );
-        let user_credit = &mut ctx.accounts.user_credit;
-        require!(user_credit.user == ctx.accounts.user.key(), CreditsError::InvalidOwner);
-        require!(user_credit.credits >= amount, CreditsError::InsufficientCredits);
-        user_credit.credits = user_credit
-            .credits
-            .checked_sub(amount)
+        require!(
+            ctx.accounts.user_token.mint == ctx.accounts.accepted_mint.mint,
+            CreditsError::InvalidMint
+        );
+        require!(
+            ctx.accounts.user_token.owner == ctx.accounts.user.key(),
+            CreditsError::InvalidOwner
+        );
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.config = ctx.accounts.config.key();
+        subscription.user = ctx.accounts.user.key();
+        subscription.user_token = ctx.accounts.user_token.key();
+        subscription.mint = ctx.accounts.accepted_mint.mint;
+        subscription.plan_id = plan_id;
+        subscription.period_secs = period_secs;
+        subscription.amount_base_units = amount_base_units;
+        subscription.next_renewal_at = Clock::get()?.unix_timestamp;
+        subscription.bump = ctx.bumps.subscription;
+
+        emit!(SubscriptionCreated {
+            config: subscription.config,
+            user: subscription.user,
+            plan_id,
+            period_secs,
+            amount_base_units,
+        });
+        Ok(())
+    }
+
+    // User ends a subscription; the token delegation should be revoked alongside.
+    pub fn cancel_subscription(_ctx: Context<CancelSubscription>) -> Result<()> {
+        Ok(())
+    }
+
+    // Permissionless crank: charge a due subscription and credit the subscriber.
+    pub fn renew_subscription(ctx: Context<RenewSubscription>) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &ctx.accounts.subscription;
+        require!(
+            now >= subscription.next_renewal_at,
+            CreditsError::RenewalNotDue
+        );
+        let user = subscription.user;
+        let amount_base_units = subscription.amount_base_units;
+        let config = &ctx.accounts.config;
+        let accepted_mint = &ctx.accounts.accepted_mint;
+        require_stable_accounts(
+            config,
+            accepted_mint,
+            &ctx.accounts.mint,
+            &ctx.accounts.user_token,
+            &ctx.accounts.vault_token,
+            user,
+            ctx.accounts.region_vault.as_ref(),
+        )?;
+        require_payment_guards(
+            config,
+            ctx.accounts.price_update.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+
+        // Charge whole credits only, so no remainder is left behind in the vault.
+        let amount_base_units =
+            whole_credit_charge(config, amount_base_units, accepted_mint.decimals)?;
+
+        let seeds = &[b"config", config.authority.as_ref(), &[config.bump]];
+        let signer = &[&seeds[..]];
+        let payer = StablePayer {
+            token_program: &ctx.accounts.token_program,
+            mint: &ctx.accounts.mint,
+            from: &ctx.accounts.user_token,
+            authority: config.to_account_info(),
+            signer_seeds: signer,
+        };
+        let received_base_units = receive_stable_payment(
+            config,
+            &payer,
+            ctx.accounts.platform_vault.as_mut(),
+            &mut ctx.accounts.vault_token,
+            amount_base_units,
+        )?;
+
+        let usd_base_units = to_usd_base_units(received_base_units, accepted_mint.decimals)?;
+        require_purchase_limits(config, usd_base_units)?;
+        let credits_to_add =
+            credits_with_remainder(config, &mut ctx.accounts.user_credit, usd_base_units)?;
+        require!(credits_to_add > 0, CreditsError::BelowMinimum);
+
+        ctx.accounts.user_credit.bump = ctx.bumps.user_credit;
+
+        // Stay on the original schedule unless the crank fell a full period behind.
+        let subscription = &mut ctx.accounts.subscription;
+        let next_renewal_at = subscription
+            .next_renewal_at
+            .checked_add(subscription.period_secs)
+            .ok_or(CreditsError::Overflow)?;
+        subscription.next_renewal_at = if next_renewal_at > now {
+            next_renewal_at
+        } else {
+            now.checked_add(subscription.period_secs)
+                .ok_or(CreditsError::Overflow)?
+        };
+
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            ctx.accounts.price_feed.as_mut(),
+            &mut ctx.accounts.user_credit,
+            CreditedPurchase {
+                kind: JournalKind::Paid,
+                user,
+                credits: credits_to_add,
+                sold_credits: credits_to_add,
+            },
+        )?;
+        let user_credit = &ctx.accounts.user_credit;
+
+        emit!(SubscriptionRenewed {
+            user,
+            plan_id: subscription.plan_id,
+            received_base_units,
+            credits_added: credits_to_add,
+            new_balance: user_credit.credits,
+            next_renewal_at: subscription.next_renewal_at,
+        });
+        Ok(())
+    }
+
+    // User opts into automatic refills pulled via a delegation to the config PDA.
+    pub fn set_auto_top_up(
+        ctx: Context<SetAutoTopUp>,
+        threshold_credits: u64,
+        amount_base_units: u64,
+    ) -> Result<()> {
+        require!(
+            threshold_credits > 0 && amount_base_units > 0,
+            CreditsError::InvalidAmount
+        );
+        require!(
+            ctx.accounts.user_token.mint == ctx.accounts.accepted_mint.mint,
+            CreditsError::InvalidMint
+        );
+        require!(
+            ctx.accounts.user_token.owner == ctx.accounts.user.key(),
+            CreditsError::InvalidOwner
+        );
+        let auto_top_up = &mut ctx.accounts.auto_top_up;
+        auto_top_up.config = ctx.accounts.config.key();
+        auto_top_up.user = ctx.accounts.user.key();
+        auto_top_up.user_token = ctx.accounts.user_token.key();
+        auto_top_up.mint = ctx.accounts.accepted_mint.mint;
+        auto_top_up.threshold_credits = threshold_credits;
+        auto_top_up.amount_base_units = amount_base_units;
+        auto_top_up.bump = ctx.bumps.auto_top_up;
+        Ok(())
+    }
+
+    // User turns off automatic refills and reclaims the rent.
+    pub fn disable_auto_top_up(_ctx: Context<DisableAutoTopUp>) -> Result<()> {
+        Ok(())
+    }
+
+    // Refill a user below their threshold; only the spender may anticipate a debit.
+    pub fn auto_top_up(ctx: Context<AutoTopUpRefill>, upcoming_debit: u64) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        let config = &ctx.accounts.config;
+        require!(
+            upcoming_debit == 0 || ctx.accounts.caller.key() == config.spender,
+            CreditsError::Unauthorized
+        );
+        let auto_top_up = &ctx.accounts.auto_top_up;
+        let user = auto_top_up.user;
+        let amount_base_units = auto_top_up.amount_base_units;
+        let projected = ctx
+            .accounts
+            .user_credit
+            .credits
+            .saturating_sub(upcoming_debit);
+        require!(
+            projected < auto_top_up.threshold_credits,
+            CreditsError::AboveTopUpThreshold
+        );
+        let accepted_mint = &ctx.accounts.accepted_mint;
+        require_stable_accounts(
+            config,
+            accepted_mint,
+            &ctx.accounts.mint,
+            &ctx.accounts.user_token,
+            &ctx.accounts.vault_token,
+            user,
+            ctx.accounts.region_vault.as_ref(),
+        )?;
+        require_payment_guards(
+            config,
+            ctx.accounts.price_update.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+
+        // Charge whole credits only, so no remainder is left behind in the vault.
+        let amount_base_units =
+            whole_credit_charge(config, amount_base_units, accepted_mint.decimals)?;
+
+        let seeds = &[b"config", config.authority.as_ref(), &[config.bump]];
+        let signer = &[&seeds[..]];
+        let payer = StablePayer {
+            token_program: &ctx.accounts.token_program,
+            mint: &ctx.accounts.mint,
+            from: &ctx.accounts.user_token,
+            authority: config.to_account_info(),
+            signer_seeds: signer,
+        };
+        let received_base_units = receive_stable_payment(
+            config,
+            &payer,
+            ctx.accounts.platform_vault.as_mut(),
+            &mut ctx.accounts.vault_token,
+            amount_base_units,
+        )?;
+
+        let usd_base_units = to_usd_base_units(received_base_units, accepted_mint.decimals)?;
+        require_purchase_limits(config, usd_base_units)?;
+        let credits_to_add =
+            credits_with_remainder(config, &mut ctx.accounts.user_credit, usd_base_units)?;
+        require!(credits_to_add > 0, CreditsError::BelowMinimum);
+
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            ctx.accounts.price_feed.as_mut(),
+            &mut ctx.accounts.user_credit,
+            CreditedPurchase {
+                kind: JournalKind::Paid,
+                user,
+                credits: credits_to_add,
+                sold_credits: credits_to_add,
+            },
+        )?;
+        let user_credit = &ctx.accounts.user_credit;
+
+        emit!(AutoToppedUp {
+            user,
+            received_base_units,
+            credits_added: credits_to_add,
+            new_balance: user_credit.credits,
+        });
+        Ok(())
+    }
+
+    // Set per-transaction purchase bounds in USD base units (max = 0 disables the cap).
+    pub fn set_purchase_limits(
+        ctx: Context<UpdateConfig>,
+        min_purchase_base_units: u64,
+        max_purchase_base_units: u64,
+    ) -> Result<()> {
+        require!(
+            max_purchase_base_units == 0 || min_purchase_base_units <= max_purchase_base_units,
+            CreditsError::InvalidAmount
+        );
+        let config = &mut ctx.accounts.config;
+        let old_min = config.min_purchase_base_units;
+        let old_max = config.max_purchase_base_units;
+        config.min_purchase_base_units = min_purchase_base_units;
+        config.max_purchase_base_units = max_purchase_base_units;
+
+        emit!(ConfigUpdated {
+            config: config.key(),
+            field: ConfigField::MinPurchase,
+            old_value: old_min,
+            new_value: min_purchase_base_units,
+        });
+        emit!(ConfigUpdated {
+            config: config.key(),
+            field: ConfigField::MaxPurchase,
+            old_value: old_max,
+            new_value: max_purchase_base_units,
+        });
+        Ok(())
+    }
+
+    // Set the share of purchased credits paid to referrers, in bps.
+    pub fn set_referral_bps(ctx: Context<UpdateConfig>, referral_bps: u16) -> Result<()> {
+        require!(
+            u64::from(referral_bps) <= BPS_DENOMINATOR,
+            CreditsError::InvalidAmount
+        );
+        let config = &mut ctx.accounts.config;
+        let old_value = u64::from(config.referral_bps);
+        config.referral_bps = referral_bps;
+
+        emit!(ConfigUpdated {
+            config: config.key(),
+            field: ConfigField::ReferralBps,
+            old_value,
+            new_value: u64::from(referral_bps),
+        });
+        Ok(())
+    }
+
+    // Set the membership NFT collection and the discount its holders get in pay_stable.
+    pub fn set_membership_discount(
+        ctx: Context<UpdatePricing>,
+        collection: Pubkey,
+        discount_bps: u16,
+    ) -> Result<()> {
+        require!(
+            u64::from(discount_bps) < BPS_DENOMINATOR,
+            CreditsError::InvalidAmount
+        );
+        let config = &mut ctx.accounts.config;
+        config.membership_collection = collection;
+        config.membership_discount_bps = discount_bps;
+        sync_price_feed(&mut ctx.accounts.price_feed, config)?;
+
+        emit!(MembershipDiscountUpdated {
+            config: config.key(),
+            collection,
+            discount_bps,
+        });
+        Ok(())
+    }
+
+    // Define a fixed-price credit bundle sold by buy_package.
+    pub fn create_package(
+        ctx: Context<CreatePackage>,
+        package_id: u32,
+        price_usd_base_units: u64,
+        credits: u64,
+        bonus_credits: u64,
+    ) -> Result<()> {
+        require!(
+            price_usd_base_units > 0 && credits > 0,
+            CreditsError::InvalidAmount
+        );
+        credits
+            .checked_add(bonus_credits)
+            .ok_or(CreditsError::Overflow)?;
+        let package = &mut ctx.accounts.package;
+        package.config = ctx.accounts.config.key();
+        package.package_id = package_id;
+        package.price_usd_base_units = price_usd_base_units;
+        package.credits = credits;
+        package.bonus_credits = bonus_credits;
+        package.bump = ctx.bumps.package;
+
+        emit!(PackageCreated {
+            config: package.config,
+            package_id,
+            price_usd_base_units,
+            credits,
+            bonus_credits,
+        });
+        Ok(())
+    }
+
+    // Retire a credit bundle and reclaim its rent.
+    pub fn close_package(ctx: Context<ClosePackage>, package_id: u32) -> Result<()> {
+        emit!(PackageClosed {
+            config: ctx.accounts.config.key(),
+            package_id,
+        });
+        Ok(())
+    }
+
+    // Sell a credit bundle at its fixed price in any accepted stablecoin.
+    pub fn buy_package(ctx: Context<BuyPackage>, _package_id: u32) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        let config = &ctx.accounts.config;
+        let accepted_mint = &ctx.accounts.accepted_mint;
+        let package = &ctx.accounts.package;
+        require_stable_accounts(
+            config,
+            accepted_mint,
+            &ctx.accounts.mint,
+            &ctx.accounts.user_token,
+            &ctx.accounts.vault_token,
+            ctx.accounts.user.key(),
+            ctx.accounts.region_vault.as_ref(),
+        )?;
+        require_payment_guards(
+            config,
+            ctx.accounts.price_update.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+
+        let amount_base_units =
+            from_usd_base_units(package.price_usd_base_units, accepted_mint.decimals)?;
+        let payer = StablePayer {
+            token_program: &ctx.accounts.token_program,
+            mint: &ctx.accounts.mint,
+            from: &ctx.accounts.user_token,
+            authority: ctx.accounts.user.to_account_info(),
+            signer_seeds: &[],
+        };
+        let received_base_units = receive_stable_payment(
+            config,
+            &payer,
+            ctx.accounts.platform_vault.as_mut(),
+            &mut ctx.accounts.vault_token,
+            amount_base_units,
+        )?;
+        // Bundles have a fixed price, so fee-bearing mints cannot buy them.
+        let usd_base_units = to_usd_base_units(received_base_units, accepted_mint.decimals)?;
+        require!(
+            usd_base_units >= package.price_usd_base_units,
+            CreditsError::InsufficientPayment
+        );
+        require_purchase_limits(config, usd_base_units)?;
+
+        let credits_to_add = package
+            .credits
+            .checked_add(package.bonus_credits)
             .ok_or(CreditsError::Overflow)?;
+        let package_id = package.package_id;
+        let package_credits = package.credits;
+        let bonus_credits = package.bonus_credits;
+
+        ctx.accounts.user_credit.last_active_at = Clock::get()?.unix_timestamp;
+        ctx.accounts.user_credit.bump = ctx.bumps.user_credit;
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            ctx.accounts.price_feed.as_mut(),
+            &mut ctx.accounts.user_credit,
+            CreditedPurchase {
+                kind: JournalKind::Paid,
+                user: ctx.accounts.user.key(),
+                credits: credits_to_add,
+                sold_credits: package_credits,
+            },
+        )?;
+        let user_credit = &ctx.accounts.user_credit;
 
-        emit!(CreditUsed {
+        emit!(PackagePurchased {
             user: ctx.accounts.user.key(),
-            amount,
+            package_id,
+            mint: ctx.accounts.mint.key(),
+            received_base_units,
+            credits_added: credits_to_add,
+            bonus_credits,
+            new_balance: user_credit.credits,
+        });
+        Ok(())
+    }
+
+    // Issue a B2B invoice: a named payer owes an exact USDC amount for fixed credits.
+    pub fn create_invoice(
+        ctx: Context<CreateInvoice>,
+        invoice_id: u64,
+        payer: Pubkey,
+        amount_base_units: u64,
+        credits: u64,
+        expires_at: i64,
+        reference_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            amount_base_units > 0 && credits > 0,
+            CreditsError::InvalidAmount
+        );
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            CreditsError::InvoiceExpired
+        );
+        let invoice = &mut ctx.accounts.invoice;
+        invoice.config = ctx.accounts.config.key();
+        invoice.invoice_id = invoice_id;
+        invoice.payer = payer;
+        let mint = ctx.accounts.config.usdc_mint;
+        invoice.mint = mint;
+        invoice.amount_base_units = amount_base_units;
+        invoice.credits = credits;
+        invoice.expires_at = expires_at;
+        invoice.reference_hash = reference_hash;
+        invoice.paid_at = 0;
+        invoice.bump = ctx.bumps.invoice;
+
+        emit!(InvoiceCreated {
+            config: invoice.config,
+            invoice_id,
+            payer,
+            mint,
+            amount_base_units,
+            credits,
+            expires_at,
+            reference_hash,
+        });
+        Ok(())
+    }
+
+    // Settle an invoice; only the exact amount, fully received, marks it paid.
+    pub fn pay_invoice(ctx: Context<PayInvoice>, _invoice_id: u64) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        let now = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.config;
+        let invoice = &ctx.accounts.invoice;
+        require!(invoice.paid_at == 0, CreditsError::InvoiceAlreadyPaid);
+        require!(now < invoice.expires_at, CreditsError::InvoiceExpired);
+        require!(
+            invoice.mint == ctx.accounts.mint.key(),
+            CreditsError::InvalidMint
+        );
+        require_stable_accounts(
+            config,
+            &ctx.accounts.accepted_mint,
+            &ctx.accounts.mint,
+            &ctx.accounts.payer_token,
+            &ctx.accounts.vault_token,
+            ctx.accounts.payer.key(),
+            ctx.accounts.region_vault.as_ref(),
+        )?;
+        require_payment_guards(
+            config,
+            ctx.accounts.price_update.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+
+        let amount_base_units = invoice.amount_base_units;
+        let payer = StablePayer {
+            token_program: &ctx.accounts.token_program,
+            mint: &ctx.accounts.mint,
+            from: &ctx.accounts.payer_token,
+            authority: ctx.accounts.payer.to_account_info(),
+            signer_seeds: &[],
+        };
+        let received_base_units = receive_stable_payment(
+            config,
+            &payer,
+            ctx.accounts.platform_vault.as_mut(),
+            &mut ctx.accounts.vault_token,
+            amount_base_units,
+        )?;
+        // Invoices settle exactly, so fee-bearing mints cannot pay them.
+        require!(
+            received_base_units == amount_base_units,
+            CreditsError::InsufficientPayment
+        );
+        let decimals = ctx.accounts.accepted_mint.decimals;
+        require_purchase_limits(config, to_usd_base_units(received_base_units, decimals)?)?;
+
+        let invoice = &mut ctx.accounts.invoice;
+        invoice.paid_at = now;
+        let credits_to_add = invoice.credits;
+
+        let user = ctx.accounts.payer.key();
+        ctx.accounts.payer_credit.last_active_at = now;
+        ctx.accounts.payer_credit.bump = ctx.bumps.payer_credit;
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            ctx.accounts.price_feed.as_mut(),
+            &mut ctx.accounts.payer_credit,
+            CreditedPurchase {
+                kind: JournalKind::Paid,
+                user,
+                credits: credits_to_add,
+                sold_credits: credits_to_add,
+            },
+        )?;
+        let user_credit = &ctx.accounts.payer_credit;
+
+        emit!(InvoicePaid {
+            config: invoice.config,
+            invoice_id: invoice.invoice_id,
+            payer: user,
+            mint: invoice.mint,
+            received_base_units,
+            credits_added: credits_to_add,
+            new_balance: user_credit.credits,
+            reference_hash: invoice.reference_hash,
+        });
+        Ok(())
+    }
+
+    // Close a paid or expired invoice and reclaim its rent.
+    pub fn close_invoice(ctx: Context<CloseInvoice>, invoice_id: u64) -> Result<()> {
+        let invoice = &ctx.accounts.invoice;
+        require!(
+            invoice.paid_at != 0 || Clock::get()?.unix_timestamp >= invoice.expires_at,
+            CreditsError::InvoiceOpen
+        );
+        emit!(InvoiceClosed {
+            config: ctx.accounts.config.key(),
+            invoice_id,
+            paid: invoice.paid_at != 0,
+        });
+        Ok(())
+    }
+
+    // Buy at a backend-signed quote; the preceding instruction must be an ed25519 verify of it.
+    pub fn pay_with_quote(
+        ctx: Context<PayWithQuote>,
+        amount_base_units: u64,
+        credits: u64,
+        expires_at: i64,
+        nonce: u64,
+    ) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        require!(
+            amount_base_units > 0 && credits > 0,
+            CreditsError::InvalidAmount
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < expires_at, CreditsError::QuoteExpired);
+        let config = &ctx.accounts.config;
+        require!(
+            config.quote_signer != Pubkey::default(),
+            CreditsError::InvalidSignature
+        );
+        let user = ctx.accounts.user.key();
+        let message = [
+            QUOTE_DOMAIN,
+            config.key().as_ref(),
+            user.as_ref(),
+            ctx.accounts.mint.key().as_ref(),
+            &amount_base_units.to_le_bytes(),
+            &credits.to_le_bytes(),
+            &expires_at.to_le_bytes(),
+            &nonce.to_le_bytes(),
+        ]
+        .concat();
+        require_ed25519_signature(&ctx.accounts.instructions, &config.quote_signer, &message)?;
+        require_stable_accounts(
+            config,
+            &ctx.accounts.accepted_mint,
+            &ctx.accounts.mint,
+            &ctx.accounts.user_token,
+            &ctx.accounts.vault_token,
+            user,
+            ctx.accounts.region_vault.as_ref(),
+        )?;
+        require_payment_guards(
+            config,
+            ctx.accounts.price_update.as_ref(),
+            Some(&ctx.accounts.instructions),
+        )?;
+
+        let payer = StablePayer {
+            token_program: &ctx.accounts.token_program,
+            mint: &ctx.accounts.mint,
+            from: &ctx.accounts.user_token,
+            authority: ctx.accounts.user.to_account_info(),
+            signer_seeds: &[],
+        };
+        let received_base_units = receive_stable_payment(
+            config,
+            &payer,
+            ctx.accounts.platform_vault.as_mut(),
+            &mut ctx.accounts.vault_token,
+            amount_base_units,
+        )?;
+        // Quotes fix the credit amount, so fee-bearing mints cannot use them.
+        require!(
+            received_base_units == amount_base_units,
+            CreditsError::InsufficientPayment
+        );
+        let decimals = ctx.accounts.accepted_mint.decimals;
+        require_purchase_limits(config, to_usd_base_units(received_base_units, decimals)?)?;
+
+        let receipt = &mut ctx.accounts.quote_receipt;
+        receipt.config = ctx.accounts.config.key();
+        receipt.nonce = nonce;
+        receipt.user = user;
+        receipt.bump = ctx.bumps.quote_receipt;
+
+        ctx.accounts.user_credit.last_active_at = now;
+        ctx.accounts.user_credit.bump = ctx.bumps.user_credit;
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            ctx.accounts.price_feed.as_mut(),
+            &mut ctx.accounts.user_credit,
+            CreditedPurchase {
+                kind: JournalKind::Paid,
+                user,
+                credits,
+                sold_credits: credits,
+            },
+        )?;
+        let user_credit = &ctx.accounts.user_credit;
+
+        emit!(QuotePaid {
+            config: ctx.accounts.config.key(),
+            user,
+            nonce,
+            mint: ctx.accounts.mint.key(),
+            received_base_units,
+            credits_added: credits,
+            new_balance: user_credit.credits,
+        });
+        Ok(())
+    }
+
+    // Relayer submits a user's off-chain permit and pays the fees; the stablecoin is
+    // pulled through the user's prior delegation to the config PDA.
+    pub fn pay_with_permit(
+        ctx: Context<PayWithPermit>,
+        amount_base_units: u64,
+        nonce: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        require!(amount_base_units > 0, CreditsError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < expires_at, CreditsError::PermitExpired);
+        let config = &ctx.accounts.config;
+        let accepted_mint = &ctx.accounts.accepted_mint;
+        let user = ctx.accounts.user.key();
+        let message = [
+            PERMIT_DOMAIN,
+            config.key().as_ref(),
+            user.as_ref(),
+            ctx.accounts.mint.key().as_ref(),
+            &amount_base_units.to_le_bytes(),
+            &nonce.to_le_bytes(),
+            &expires_at.to_le_bytes(),
+        ]
+        .concat();
+        require_ed25519_signature(&ctx.accounts.instructions, &user, &message)?;
+        require_stable_accounts(
+            config,
+            accepted_mint,
+            &ctx.accounts.mint,
+            &ctx.accounts.user_token,
+            &ctx.accounts.vault_token,
+            user,
+            ctx.accounts.region_vault.as_ref(),
+        )?;
+        require_payment_guards(
+            config,
+            ctx.accounts.price_update.as_ref(),
+            Some(&ctx.accounts.instructions),
+        )?;
+        require_purchase_limits(
+            config,
+            to_usd_base_units(amount_base_units, accepted_mint.decimals)?,
+        )?;
+
+        // Charge whole credits only, so no remainder is left behind in the vault.
+        let amount_base_units =
+            whole_credit_charge(config, amount_base_units, accepted_mint.decimals)?;
+
+        let seeds = &[b"config", config.authority.as_ref(), &[config.bump]];
+        let signer = &[&seeds[..]];
+        let payer = StablePayer {
+            token_program: &ctx.accounts.token_program,
+            mint: &ctx.accounts.mint,
+            from: &ctx.accounts.user_token,
+            authority: config.to_account_info(),
+            signer_seeds: signer,
+        };
+        let received_base_units = receive_stable_payment(
+            config,
+            &payer,
+            ctx.accounts.platform_vault.as_mut(),
+            &mut ctx.accounts.vault_token,
+            amount_base_units,
+        )?;
+
+        let usd_base_units = to_usd_base_units(received_base_units, accepted_mint.decimals)?;
+        let credits_to_add =
+            credits_with_remainder(config, &mut ctx.accounts.user_credit, usd_base_units)?;
+        require!(credits_to_add > 0, CreditsError::BelowMinimum);
+
+        let receipt = &mut ctx.accounts.permit_receipt;
+        receipt.config = ctx.accounts.config.key();
+        receipt.user = user;
+        receipt.nonce = nonce;
+        receipt.relayer = ctx.accounts.relayer.key();
+        receipt.bump = ctx.bumps.permit_receipt;
+
+        // Relayed, so the user did not sign this transaction: leave last_active_at alone.
+        ctx.accounts.user_credit.bump = ctx.bumps.user_credit;
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            ctx.accounts.price_feed.as_mut(),
+            &mut ctx.accounts.user_credit,
+            CreditedPurchase {
+                kind: JournalKind::Paid,
+                user,
+                credits: credits_to_add,
+                sold_credits: credits_to_add,
+            },
+        )?;
+        let user_credit = &ctx.accounts.user_credit;
+
+        emit!(PermitPaid {
+            config: ctx.accounts.config.key(),
+            user,
+            relayer: ctx.accounts.relayer.key(),
+            nonce,
+            mint: ctx.accounts.mint.key(),
+            received_base_units,
+            credits_added: credits_to_add,
+            new_balance: user_credit.credits,
+        });
+        Ok(())
+    }
+
+    // Create a time-boxed bonus campaign applied by pay_stable inside its window.
+    pub fn create_campaign(
+        ctx: Context<CreateCampaign>,
+        campaign_id: u32,
+        starts_at: i64,
+        ends_at: i64,
+        bonus_bps: u16,
+    ) -> Result<()> {
+        require!(starts_at < ends_at, CreditsError::InvalidCampaignWindow);
+        require!(
+            bonus_bps > 0 && u64::from(bonus_bps) <= BPS_DENOMINATOR,
+            CreditsError::InvalidAmount
+        );
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.config = ctx.accounts.config.key();
+        campaign.campaign_id = campaign_id;
+        campaign.starts_at = starts_at;
+        campaign.ends_at = ends_at;
+        campaign.bonus_bps = bonus_bps;
+        campaign.bump = ctx.bumps.campaign;
+
+        emit!(CampaignUpdated {
+            config: campaign.config,
+            campaign_id,
+            starts_at,
+            ends_at,
+            bonus_bps,
+        });
+        Ok(())
+    }
+
+    // End a campaign early; it stays on-chain until closed.
+    pub fn cancel_campaign(ctx: Context<UpdateCampaign>, _campaign_id: u32) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let campaign = &mut ctx.accounts.campaign;
+        require!(now < campaign.ends_at, CreditsError::CampaignInactive);
+        campaign.ends_at = now.max(campaign.starts_at);
+
+        emit!(CampaignUpdated {
+            config: campaign.config,
+            campaign_id: campaign.campaign_id,
+            starts_at: campaign.starts_at,
+            ends_at: campaign.ends_at,
+            bonus_bps: campaign.bonus_bps,
+        });
+        Ok(())
+    }
+
+    // Close an expired campaign and reclaim its rent.
+    pub fn close_campaign(ctx: Context<CloseCampaign>, _campaign_id: u32) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.campaign.ends_at,
+            CreditsError::CampaignStillActive
+        );
+        Ok(())
+    }
+
+    // Create a promo voucher redeemable in pay_stable by whoever knows the code.
+    pub fn create_voucher(
+        ctx: Context<CreateVoucher>,
+        code_hash: [u8; 32],
+        discount_bps: u16,
+        bonus_credits: u64,
+        max_uses: u32,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(
+            u64::from(discount_bps) < BPS_DENOMINATOR,
+            CreditsError::InvalidAmount
+        );
+        require!(
+            max_uses > 0 && (discount_bps > 0 || bonus_credits > 0),
+            CreditsError::InvalidAmount
+        );
+        let voucher = &mut ctx.accounts.voucher;
+        voucher.config = ctx.accounts.config.key();
+        voucher.code_hash = code_hash;
+        voucher.discount_bps = discount_bps;
+        voucher.bonus_credits = bonus_credits;
+        voucher.max_uses = max_uses;
+        voucher.uses = 0;
+        voucher.expires_at = expires_at;
+        voucher.bump = ctx.bumps.voucher;
+        Ok(())
+    }
+
+    // Retire a voucher and reclaim its rent.
+    pub fn close_voucher(_ctx: Context<CloseVoucher>) -> Result<()> {
+        Ok(())
+    }
+
+    // Register a prepaid card sold offline; only the hash of its 16-32 char serial is stored.
+    pub fn create_card(
+        ctx: Context<CreateCard>,
+        serial_hash: [u8; 32],
+        credits: u64,
+    ) -> Result<()> {
+        require!(credits > 0, CreditsError::InvalidAmount);
+        let card = &mut ctx.accounts.card;
+        card.config = ctx.accounts.config.key();
+        card.serial_hash = serial_hash;
+        card.credits = credits;
+        card.bump = ctx.bumps.card;
+        Ok(())
+    }
+
+    // Commit to hash(config, user, serial) ahead of redeeming, so a revealed serial
+    // cannot be front-run by another wallet.
+    pub fn commit_card(ctx: Context<CommitCard>, commitment: [u8; 32]) -> Result<()> {
+        let card_commit = &mut ctx.accounts.card_commit;
+        card_commit.config = ctx.accounts.config.key();
+        card_commit.user = ctx.accounts.user.key();
+        card_commit.commitment = commitment;
+        card_commit.slot = Clock::get()?.slot;
+        card_commit.bump = ctx.bumps.card_commit;
+        Ok(())
+    }
+
+    // Redeem a prepaid card once against an earlier-slot commitment; the card PDA is
+    // closed back to the authority and the commitment back to the user.
+    pub fn redeem_card(ctx: Context<RedeemCard>, serial: String) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        require!(
+            (MIN_CARD_SERIAL_LEN..=MAX_CARD_SERIAL_LEN).contains(&serial.len()),
+            CreditsError::InvalidCardSerial
+        );
+        let card = &ctx.accounts.card;
+        require!(
+            hashv(&[serial.as_bytes()]).to_bytes() == card.serial_hash,
+            CreditsError::InvalidCardSerial
+        );
+        let card_commit = &ctx.accounts.card_commit;
+        let commitment = hashv(&[
+            ctx.accounts.config.key().as_ref(),
+            ctx.accounts.user.key().as_ref(),
+            serial.as_bytes(),
+        ])
+        .to_bytes();
+        require!(
+            card_commit.commitment == commitment && card_commit.slot < Clock::get()?.slot,
+            CreditsError::InvalidCardCommitment
+        );
+        let credits_to_add = card.credits;
+
+        let user = ctx.accounts.user.key();
+        ctx.accounts.user_credit.last_active_at = Clock::get()?.unix_timestamp;
+        ctx.accounts.user_credit.bump = ctx.bumps.user_credit;
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            None,
+            &mut ctx.accounts.user_credit,
+            CreditedPurchase {
+                kind: JournalKind::PrepaidCard,
+                user,
+                credits: credits_to_add,
+                sold_credits: 0,
+            },
+        )?;
+        let user_credit = &ctx.accounts.user_credit;
+
+        emit!(CardRedeemed {
+            config: ctx.accounts.config.key(),
+            user,
+            serial_hash: ctx.accounts.card.serial_hash,
+            credits_added: credits_to_add,
             new_balance: user_credit.credits,
         });
         Ok(())
     }
+
+    // Replace the volume-tier bonus table applied by pay_stable.
+    pub fn set_price_tiers(ctx: Context<SetPriceTiers>, tiers: Vec<PriceTier>) -> Result<()> {
+        require!(
+            tiers.len() <= MAX_PRICE_TIERS,
+            CreditsError::TooManyPriceTiers
+        );
+        for (index, tier) in tiers.iter().enumerate() {
+            require!(
+                u64::from(tier.bonus_bps) <= BPS_DENOMINATOR,
+                CreditsError::InvalidPriceTier
+            );
+            if index > 0 {
+                require!(
+                    tier.min_usd_base_units > tiers[index - 1].min_usd_base_units,
+                    CreditsError::InvalidPriceTier
+                );
+            }
+        }
+
+        let price_tiers = &mut ctx.accounts.price_tiers;
+        price_tiers.config = ctx.accounts.config.key();
+        price_tiers.tier_count = tiers.len() as u8;
+        price_tiers.tiers = [PriceTier::default(); MAX_PRICE_TIERS];
+        price_tiers.tiers[..tiers.len()].copy_from_slice(&tiers);
+        price_tiers.bump = ctx.bumps.price_tiers;
+        sync_price_feed(&mut ctx.accounts.price_feed, &ctx.accounts.config)
+    }
+
+    // Replace the KANG holder bonus table; tiers are ordered by ascending balance.
+    pub fn set_holder_tiers(
+        ctx: Context<SetHolderTiers>,
+        mint: Pubkey,
+        tiers: Vec<HolderTier>,
+    ) -> Result<()> {
+        require!(
+            tiers.len() <= MAX_HOLDER_TIERS,
+            CreditsError::TooManyPriceTiers
+        );
+        for (index, tier) in tiers.iter().enumerate() {
+            require!(
+                u64::from(tier.bonus_bps) <= BPS_DENOMINATOR,
+                CreditsError::InvalidPriceTier
+            );
+            if index > 0 {
+                require!(
+                    tier.min_balance > tiers[index - 1].min_balance,
+                    CreditsError::InvalidPriceTier
+                );
+            }
+        }
+
+        let holder_tiers = &mut ctx.accounts.holder_tiers;
+        holder_tiers.config = ctx.accounts.config.key();
+        holder_tiers.mint = mint;
+        holder_tiers.tier_count = tiers.len() as u8;
+        holder_tiers.tiers = [HolderTier::default(); MAX_HOLDER_TIERS];
+        holder_tiers.tiers[..tiers.len()].copy_from_slice(&tiers);
+        holder_tiers.bump = ctx.bumps.holder_tiers;
+        Ok(())
+    }
+
+    // Register the backend key whose signed quotes pay_with_quote honors (default disables).
+    pub fn set_quote_signer(ctx: Context<UpdateConfig>, quote_signer: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.quote_signer = quote_signer;
+        emit!(QuoteSignerUpdated {
+            config: config.key(),
+            quote_signer,
+        });
+        Ok(())
+    }
+
+    // Switch between flat credit_unit pricing and a stepped curve over credits sold.
+    pub fn set_pricing_mode(
+        ctx: Context<UpdatePricing>,
+        mode: PricingMode,
+        curve_base_unit: u64,
+        curve_step_credits: u64,
+        curve_step_bps: u16,
+        curve_max_unit: u64,
+    ) -> Result<()> {
+        if mode == PricingMode::Curve {
+            require!(
+                (MIN_CREDIT_UNIT..=MAX_CREDIT_UNIT).contains(&curve_base_unit)
+                    && (curve_base_unit..=MAX_CREDIT_UNIT).contains(&curve_max_unit)
+                    && curve_step_credits > 0,
+                CreditsError::InvalidCreditUnit
+            );
+        }
+        let config = &mut ctx.accounts.config;
+        config.pricing_mode = mode;
+        config.curve_base_unit = curve_base_unit;
+        config.curve_step_credits = curve_step_credits;
+        config.curve_step_bps = curve_step_bps;
+        config.curve_max_unit = curve_max_unit;
+        sync_price_feed(&mut ctx.accounts.price_feed, config)?;
+
+        emit!(PricingModeUpdated {
+            config: config.key(),
+            mode,
+            curve_base_unit,
+            curve_step_credits,
+            curve_step_bps,
+            curve_max_unit,
+        });
+        Ok(())
+    }
+
+    // Replace the A/B pricing variants; buyers are bucketed into one by wallet key.
+    pub fn set_pricing_variants(
+        ctx: Context<UpdatePricing>,
+        variants: Vec<PricingVariant>,
+    ) -> Result<()> {
+        require!(
+            variants.len() <= MAX_PRICING_VARIANTS,
+            CreditsError::InvalidPricingVariant
+        );
+        for (index, variant) in variants.iter().enumerate() {
+            require!(
+                u64::from(variant.bonus_bps) <= BPS_DENOMINATOR
+                    && !variants[..index]
+                        .iter()
+                        .any(|other| other.variant_id == variant.variant_id),
+                CreditsError::InvalidPricingVariant
+            );
+        }
+
+        let config = &mut ctx.accounts.config;
+        config.variant_count = variants.len() as u8;
+        config.pricing_variants = [PricingVariant::default(); MAX_PRICING_VARIANTS];
+        config.pricing_variants[..variants.len()].copy_from_slice(&variants);
+        sync_price_feed(&mut ctx.accounts.price_feed, config)?;
+
+        emit!(PricingVariantsUpdated {
+            config: config.key(),
+            variants,
+        });
+        Ok(())
+    }
+
+    // Cap the stacked purchase bonuses at `max_bonus_bps` of base credits (0 = uncapped).
+    pub fn set_max_bonus_bps(ctx: Context<UpdatePricing>, max_bonus_bps: u16) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let old_value = u64::from(config.max_bonus_bps);
+        config.max_bonus_bps = max_bonus_bps;
+        sync_price_feed(&mut ctx.accounts.price_feed, config)?;
+
+        emit!(ConfigUpdated {
+            config: config.key(),
+            field: ConfigField::MaxBonusBps,
+            old_value,
+            new_value: u64::from(max_bonus_bps),
+        });
+        Ok(())
+    }
+
+    // Configure the Pyth SOL/USD feed used by pay_sol.
+    pub fn set_sol_price_feed(
+        ctx: Context<UpdateConfig>,
+        feed_id: [u8; 32],
+        max_age_secs: u64,
+        max_conf_bps: u16,
+    ) -> Result<()> {
+        require!(
+            u64::from(max_conf_bps) <= BPS_DENOMINATOR,
+            CreditsError::InvalidAmount
+        );
+        let config = &mut ctx.accounts.config;
+        config.sol_feed_id = feed_id;
+        config.sol_max_age_secs = max_age_secs;
+        config.sol_max_conf_bps = max_conf_bps;
+        Ok(())
+    }
+
+    // Create the config's canonical address lookup table, owned by a program PDA.
+    pub fn create_lookup_table(ctx: Context<CreateLookupTable>, recent_slot: u64) -> Result<()> {
+        let config_key = ctx.accounts.config.key();
+        let (create_ix, table_address) = alt_instruction::create_lookup_table(
+            ctx.accounts.lookup_table_authority.key(),
+            ctx.accounts.authority.key(),
+            recent_slot,
+        );
+        require!(
+            table_address == ctx.accounts.lookup_table.key(),
+            CreditsError::InvalidLookupTable
+        );
+        invoke_signed(
+            &create_ix,
+            &[
+                ctx.accounts.lookup_table.to_account_info(),
+                ctx.accounts.lookup_table_authority.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[&[
+                b"alt_authority",
+                config_key.as_ref(),
+                &[ctx.bumps.lookup_table_authority],
+            ]],
+        )?;
+
+        ctx.accounts.config.lookup_table = table_address;
+        emit!(LookupTableUpdated {
+            config: config_key,
+            lookup_table: table_address,
+            added: 0,
+            active: true,
+        });
+        Ok(())
+    }
+
+    // Append addresses to the config's lookup table; the authority funds the realloc.
+    pub fn extend_lookup_table(
+        ctx: Context<ManageLookupTable>,
+        addresses: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(
+            !addresses.is_empty() && addresses.len() <= MAX_LOOKUP_TABLE_EXTEND,
+            CreditsError::InvalidAmount
+        );
+        let config_key = ctx.accounts.config.key();
+        let added = addresses.len() as u32;
+        let extend_ix = alt_instruction::extend_lookup_table(
+            ctx.accounts.lookup_table.key(),
+            ctx.accounts.lookup_table_authority.key(),
+            Some(ctx.accounts.authority.key()),
+            addresses,
+        );
+        invoke_signed(
+            &extend_ix,
+            &[
+                ctx.accounts.lookup_table.to_account_info(),
+                ctx.accounts.lookup_table_authority.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[&[
+                b"alt_authority",
+                config_key.as_ref(),
+                &[ctx.bumps.lookup_table_authority],
+            ]],
+        )?;
+
+        emit!(LookupTableUpdated {
+            config: config_key,
+            lookup_table: ctx.accounts.lookup_table.key(),
+            added,
+            active: true,
+        });
+        Ok(())
+    }
+
+    // Deactivate the config's lookup table ahead of replacing or closing it.
+    pub fn deactivate_lookup_table(ctx: Context<ManageLookupTable>) -> Result<()> {
+        let config_key = ctx.accounts.config.key();
+        let deactivate_ix = alt_instruction::deactivate_lookup_table(
+            ctx.accounts.lookup_table.key(),
+            ctx.accounts.lookup_table_authority.key(),
+        );
+        invoke_signed(
+            &deactivate_ix,
+            &[
+                ctx.accounts.lookup_table.to_account_info(),
+                ctx.accounts.lookup_table_authority.to_account_info(),
+            ],
+            &[&[
+                b"alt_authority",
+                config_key.as_ref(),
+                &[ctx.bumps.lookup_table_authority],
+            ]],
+        )?;
+
+        ctx.accounts.config.lookup_table = Pubkey::default();
+        emit!(LookupTableUpdated {
+            config: config_key,
+            lookup_table: ctx.accounts.lookup_table.key(),
+            added: 0,
+            active: false,
+        });
+        Ok(())
+    }
+
+    // Close a deactivated lookup table once its cooldown has passed, refunding the authority.
+    pub fn close_lookup_table(ctx: Context<CloseLookupTable>) -> Result<()> {
+        let config_key = ctx.accounts.config.key();
+        require!(
+            ctx.accounts.lookup_table.key() != ctx.accounts.config.lookup_table,
+            CreditsError::InvalidLookupTable
+        );
+        let close_ix = alt_instruction::close_lookup_table(
+            ctx.accounts.lookup_table.key(),
+            ctx.accounts.lookup_table_authority.key(),
+            ctx.accounts.authority.key(),
+        );
+        invoke_signed(
+            &close_ix,
+            &[
+                ctx.accounts.lookup_table.to_account_info(),
+                ctx.accounts.lookup_table_authority.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+            ],
+            &[&[
+                b"alt_authority",
+                config_key.as_ref(),
+                &[ctx.bumps.lookup_table_authority],
+            ]],
+        )?;
+        Ok(())
+    }
+
+    // Create the config's journal and require it on every balance mutation.
+    pub fn initialize_journal(ctx: Context<InitializeJournal>) -> Result<()> {
+        let journal = &mut ctx.accounts.journal;
+        journal.config = ctx.accounts.config.key();
+        journal.next_seq = 0;
+        journal.bump = ctx.bumps.journal;
+        ctx.accounts.config.journal_enabled = true;
+        Ok(())
+    }
+
+    // Create the program-owned vault that receives SOL purchases.
+    pub fn initialize_sol_vault(ctx: Context<InitializeSolVault>) -> Result<()> {
+        let sol_vault = &mut ctx.accounts.sol_vault;
+        sol_vault.config = ctx.accounts.config.key();
+        sol_vault.bump = ctx.bumps.sol_vault;
+        Ok(())
+    }
+
+    // Accept a user SOL transfer priced via Pyth and mint credits.
+    pub fn pay_sol(ctx: Context<PaySol>, lamports: u64) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        require!(lamports > 0, CreditsError::InvalidAmount);
+        let config = &ctx.accounts.config;
+        require!(
+            config.sol_feed_id != [0u8; 32],
+            CreditsError::SolPaymentsDisabled
+        );
+        require_cpi_guard(config, ctx.accounts.instructions.as_ref())?;
+
+        let price = ctx.accounts.price_update.get_price_no_older_than(
+            &Clock::get()?,
+            config.sol_max_age_secs,
+            &config.sol_feed_id,
+        )?;
+        require!(
+            price.price > 0 && price.exponent <= 0,
+            CreditsError::InvalidPrice
+        );
+        let price_value = price.price.unsigned_abs();
+        require!(
+            u128::from(price.conf) * u128::from(BPS_DENOMINATOR)
+                <= u128::from(price_value) * u128::from(config.sol_max_conf_bps),
+            CreditsError::PriceTooUncertain
+        );
+
+        // usd_base_units = lamports * price * 10^expo * 10^6 / 10^9
+        let scale = 10u128
+            .checked_pow(price.exponent.unsigned_abs())
+            .ok_or(CreditsError::Overflow)?
+            * LAMPORTS_PER_SOL;
+        let usd_base_units = u128::from(lamports)
+            .checked_mul(u128::from(price_value))
+            .and_then(|value| value.checked_mul(USD_BASE_UNITS))
+            .ok_or(CreditsError::Overflow)?
+            / scale;
+        let usd_base_units =
+            u64::try_from(usd_base_units).map_err(|_| error!(CreditsError::Overflow))?;
+        require_purchase_limits(config, usd_base_units)?;
+        let credits_to_add =
+            credits_with_remainder(config, &mut ctx.accounts.user_credit, usd_base_units)?;
+        require!(credits_to_add > 0, CreditsError::BelowMinimum);
+
+        let cpi_accounts = system_program::Transfer {
+            from: ctx.accounts.user.to_account_info(),
+            to: ctx.accounts.sol_vault.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.system_program.to_account_info();
+        system_program::transfer(CpiContext::new(cpi_program, cpi_accounts), lamports)?;
+
+        ctx.accounts.user_credit.last_active_at = Clock::get()?.unix_timestamp;
+        ctx.accounts.user_credit.bump = ctx.bumps.user_credit;
+        credit_purchase(
+            &mut ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            ctx.accounts.price_feed.as_mut(),
+            &mut ctx.accounts.user_credit,
+            CreditedPurchase {
+                kind: JournalKind::PaidSol,
+                user: ctx.accounts.user.key(),
+                credits: credits_to_add,
+                sold_credits: credits_to_add,
+            },
+        )?;
+        let user_credit = &ctx.accounts.user_credit;
+
+        emit!(PaidSol {
+            user: ctx.accounts.user.key(),
+            lamports,
+            price: price.price,
+            exponent: price.exponent,
+            credits_added: credits_to_add,
+            new_balance: user_credit.credits,
+        });
+        Ok(())
+    }
+
+    // Allow the authority to withdraw SOL from the vault, keeping it rent exempt.
+    pub fn withdraw_sol(ctx: Context<WithdrawSol>, lamports: u64) -> Result<()> {
+        require!(!ctx.accounts.global.frozen, CreditsError::GloballyFrozen);
+        require!(lamports > 0, CreditsError::InvalidAmount);
+        let sol_vault = ctx.accounts.sol_vault.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(sol_vault.data_len());
+        let available = sol_vault.lamports().saturating_sub(rent_minimum);
+        require!(
+            lamports <= available,
+            CreditsError::InsufficientVaultBalance
+        );
+
+        **sol_vault.try_borrow_mut_lamports()? -= lamports;
+        **ctx.accounts.treasury.try_borrow_mut_lamports()? += lamports;
+
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::WithdrawnSol,
+            ctx.accounts.authority.key(),
+            lamports,
+        )?;
+
+        emit!(SolWithdrawn {
+            authority: ctx.accounts.authority.key(),
+            treasury: ctx.accounts.treasury.key(),
+            lamports,
+        });
+        Ok(())
+    }
+
+    // Allow the authority to withdraw USDC from the vault.
+    pub fn withdraw_usdc(ctx: Context<WithdrawUsdc>, amount_base_units: u64) -> Result<()> {
+        require!(!ctx.accounts.global.frozen, CreditsError::GloballyFrozen);
+        require!(amount_base_units > 0, CreditsError::InvalidAmount);
+        let config = &ctx.accounts.config;
+        require!(
+            ctx.accounts.usdc_mint.key() == config.usdc_mint,
+            CreditsError::InvalidMint
+        );
+        require!(
+            ctx.accounts.treasury_usdc.mint == config.usdc_mint,
+            CreditsError::InvalidMint
+        );
+        require!(
+            ctx.accounts.vault_usdc.mint == config.usdc_mint,
+            CreditsError::InvalidMint
+        );
+        require!(
+            ctx.accounts.vault_usdc.owner == config.key(),
+            CreditsError::InvalidOwner
+        );
+        if let Some(region_vault) = ctx.accounts.region_vault.as_ref() {
+            require!(
+                region_vault.vault_token == ctx.accounts.vault_usdc.key(),
+                CreditsError::InvalidRegionVault
+            );
+        }
+
+        let seeds = &[b"config", config.authority.as_ref(), &[config.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault_usdc.to_account_info(),
+            mint: ctx.accounts.usdc_mint.to_account_info(),
+            to: ctx.accounts.treasury_usdc.to_account_info(),
+            authority: ctx.accounts.config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            amount_base_units,
+            ctx.accounts.usdc_mint.decimals,
+        )?;
+
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::WithdrawnStable,
+            ctx.accounts.authority.key(),
+            amount_base_units,
+        )?;
+
+        emit!(Withdrawn {
+            authority: ctx.accounts.authority.key(),
+            vault: ctx.accounts.vault_usdc.key(),
+            mint: ctx.accounts.usdc_mint.key(),
+            amount_base_units,
+        });
+        Ok(())
+    }
+
+    // Allow the authority to withdraw any accepted stablecoin from one of its vaults.
+    pub fn withdraw_stable(ctx: Context<WithdrawStable>, amount_base_units: u64) -> Result<()> {
+        require!(!ctx.accounts.global.frozen, CreditsError::GloballyFrozen);
+        require!(amount_base_units > 0, CreditsError::InvalidAmount);
+        let config = &ctx.accounts.config;
+        let mint = ctx.accounts.accepted_mint.mint;
+        require!(
+            ctx.accounts.treasury_token.mint == mint,
+            CreditsError::InvalidMint
+        );
+        require!(
+            ctx.accounts.vault_token.mint == mint,
+            CreditsError::InvalidMint
+        );
+        require!(
+            ctx.accounts.vault_token.owner == config.key(),
+            CreditsError::InvalidOwner
+        );
+        if let Some(region_vault) = ctx.accounts.region_vault.as_ref() {
+            require!(
+                region_vault.vault_token == ctx.accounts.vault_token.key(),
+                CreditsError::InvalidRegionVault
+            );
+        }
+
+        let seeds = &[b"config", config.authority.as_ref(), &[config.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault_token.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.treasury_token.to_account_info(),
+            authority: ctx.accounts.config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            amount_base_units,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::WithdrawnStable,
+            ctx.accounts.authority.key(),
+            amount_base_units,
+        )?;
+
+        emit!(Withdrawn {
+            authority: ctx.accounts.authority.key(),
+            vault: ctx.accounts.vault_token.key(),
+            mint,
+            amount_base_units,
+        });
+        Ok(())
+    }
+
+    // Permissionless: snapshot vault balances, in USD base units, against outstanding credits.
+    pub fn attest_reserves<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AttestReserves<'info>>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        // Remaining accounts are (RegionVault, vault token) pairs covering every region,
+        // followed by (AcceptedMint, vault token) pairs covering every accepted mint.
+        let region_accounts = usize::from(config.region_count) * 2;
+        require!(
+            ctx.remaining_accounts.len()
+                == region_accounts + usize::from(config.accepted_mint_count) * 2,
+            CreditsError::MissingVault
+        );
+        let (region_pairs, mint_pairs) = ctx.remaining_accounts.split_at(region_accounts);
+        let mut counted = vec![ctx.accounts.vault_usdc.key()];
+        let mut vault_balance = to_usd_base_units(
+            ctx.accounts.vault_usdc.amount,
+            ctx.accounts.usdc_mint.decimals,
+        )?;
+        for pair in region_pairs.chunks(2) {
+            let region_vault = Account::<RegionVault>::try_from(&pair[0])?;
+            let vault_token = InterfaceAccount::<TokenAccount>::try_from(&pair[1])?;
+            require!(
+                region_vault.config == config.key()
+                    && region_vault.vault_token == vault_token.key()
+                    && !counted.contains(&vault_token.key()),
+                CreditsError::InvalidRegionVault
+            );
+            counted.push(vault_token.key());
+            vault_balance = vault_balance
+                .checked_add(to_usd_base_units(
+                    vault_token.amount,
+                    region_vault.decimals,
+                )?)
+                .ok_or(CreditsError::Overflow)?;
+        }
+        let mut seen_mints = Vec::with_capacity(usize::from(config.accepted_mint_count));
+        for pair in mint_pairs.chunks(2) {
+            let accepted_mint = Account::<AcceptedMint>::try_from(&pair[0])?;
+            let vault_token = InterfaceAccount::<TokenAccount>::try_from(&pair[1])?;
+            require!(
+                accepted_mint.config == config.key()
+                    && accepted_mint.vault == vault_token.key()
+                    && !seen_mints.contains(&accepted_mint.mint),
+                CreditsError::InvalidVault
+            );
+            seen_mints.push(accepted_mint.mint);
+            // The USDC vault or a regional vault may double as a mint vault; count it once.
+            if counted.contains(&vault_token.key()) {
+                continue;
+            }
+            counted.push(vault_token.key());
+            vault_balance = vault_balance
+                .checked_add(to_usd_base_units(
+                    vault_token.amount,
+                    accepted_mint.decimals,
+                )?)
+                .ok_or(CreditsError::Overflow)?;
+        }
+        // SOL is reported in lamports rather than priced, net of the vault's rent reserve.
+        let sol_vault_lamports = match ctx.accounts.sol_vault.as_ref() {
+            Some(sol_vault) => {
+                let info = sol_vault.to_account_info();
+                info.lamports()
+                    .saturating_sub(Rent::get()?.minimum_balance(info.data_len()))
+            }
+            None => {
+                require!(
+                    config.sol_feed_id == [0u8; 32],
+                    CreditsError::MissingSolVault
+                );
+                0
+            }
+        };
+
+        let liabilities_credits = config
+            .total_credits_issued
+            .saturating_sub(config.total_credits_consumed);
+        let credit_unit = current_credit_unit(config)?;
+        let liabilities_base_units = liabilities_credits
+            .checked_mul(credit_unit)
+            .ok_or(CreditsError::Overflow)?;
+        let stats_hash = hashv(&[
+            &config.total_credits_issued.to_le_bytes(),
+            &config.total_credits_consumed.to_le_bytes(),
+            &credit_unit.to_le_bytes(),
+        ])
+        .to_bytes();
+        let clock = Clock::get()?;
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.config = config.key();
+        attestation.index = config.attestation_count;
+        attestation.vault_balance = vault_balance;
+        attestation.sol_vault_lamports = sol_vault_lamports;
+        attestation.liabilities_credits = liabilities_credits;
+        attestation.liabilities_base_units = liabilities_base_units;
+        attestation.stats_hash = stats_hash;
+        attestation.timestamp = clock.unix_timestamp;
+        attestation.slot = clock.slot;
+        attestation.bump = ctx.bumps.attestation;
+        config.attestation_count = config
+            .attestation_count
+            .checked_add(1)
+            .ok_or(CreditsError::Overflow)?;
+
+        emit!(ReservesAttested {
+            config: attestation.config,
+            index: attestation.index,
+            vault_balance: attestation.vault_balance,
+            sol_vault_lamports,
+            liabilities_base_units,
+            timestamp: attestation.timestamp,
+        });
+        Ok(())
+    }
+
+    // Permissionless: fund a program-owned account up to its rent-exempt minimum.
+    pub fn top_up_rent(ctx: Context<TopUpRent>) -> Result<()> {
+        let target = ctx.accounts.target.to_account_info();
+        let required = Rent::get()?.minimum_balance(target.data_len());
+        let shortfall = required.saturating_sub(target.lamports());
+        require!(shortfall > 0, CreditsError::AlreadyRentExempt);
+
+        let cpi_accounts = system_program::Transfer {
+            from: ctx.accounts.payer.to_account_info(),
+            to: target,
+        };
+        let cpi_program = ctx.accounts.system_program.to_account_info();
+        system_program::transfer(CpiContext::new(cpi_program, cpi_accounts), shortfall)?;
+
+        emit!(RentToppedUp {
+            account: ctx.accounts.target.key(),
+            payer: ctx.accounts.payer.key(),
+            lamports: shortfall,
+        });
+        Ok(())
+    }
+
+    // Grow a Config written by an older layout to the current size; new fields start zeroed.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        migrate_account(
+            &ctx.accounts.authority,
+            &ctx.accounts.config,
+            &ctx.accounts.system_program,
+            Config::DISCRIMINATOR,
+            8 + Config::INIT_SPACE,
+        )
+    }
+
+    // Permissionless: anyone may pay to bring an older UserCredit up to the current layout.
+    pub fn migrate_user_credit(ctx: Context<MigrateUserCredit>) -> Result<()> {
+        migrate_account(
+            &ctx.accounts.payer,
+            &ctx.accounts.user_credit,
+            &ctx.accounts.system_program,
+            UserCredit::DISCRIMINATOR,
+            8 + UserCredit::INIT_SPACE,
+        )
+    }
+
+    // Devnet-only: grant a small daily credit allowance per wallet.
+    #[cfg(feature = "devnet-faucet")]
+    pub fn faucet(ctx: Context<Faucet>) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        let day = Clock::get()?.unix_timestamp / SECONDS_PER_DAY;
+        let claim = &mut ctx.accounts.faucet_claim;
+        require!(
+            claim.user == Pubkey::default() || claim.last_claim_day < day,
+            CreditsError::FaucetAlreadyClaimed
+        );
+        claim.user = ctx.accounts.user.key();
+        claim.last_claim_day = day;
+        claim.bump = ctx.bumps.faucet_claim;
+
+        let user_credit = &mut ctx.accounts.user_credit;
+        user_credit.user = ctx.accounts.user.key();
+        user_credit.credits = user_credit
+            .credits
+            .checked_add(FAUCET_CREDITS_PER_DAY)
+            .ok_or(CreditsError::Overflow)?;
+        user_credit.last_active_at = Clock::get()?.unix_timestamp;
+        user_credit.bump = ctx.bumps.user_credit;
+
+        let config = &mut ctx.accounts.config;
+        config.total_credits_issued = config
+            .total_credits_issued
+            .checked_add(FAUCET_CREDITS_PER_DAY)
+            .ok_or(CreditsError::Overflow)?;
+
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::Faucet,
+            ctx.accounts.user.key(),
+            FAUCET_CREDITS_PER_DAY,
+        )?;
+
+        emit!(FaucetClaimed {
+            user: ctx.accounts.user.key(),
+            credits_added: FAUCET_CREDITS_PER_DAY,
+            new_balance: user_credit.credits,
+        });
+        emit_balance_changed(
+            ctx.accounts.user.key(),
+            BalanceCategory::Faucet,
+            FAUCET_CREDITS_PER_DAY,
+            user_credit.credits,
+        )?;
+        Ok(())
+    }
+
+    // User moves credits to another wallet, creating its credit account if needed.
+    pub fn gift_credits(
+        ctx: Context<GiftCredits>,
+        amount: u64,
+        memo: Option<String>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.global.frozen, CreditsError::GloballyFrozen);
+        require_cpi_guard(&ctx.accounts.config, ctx.accounts.instructions.as_ref())?;
+        require!(amount > 0, CreditsError::InvalidAmount);
+        let memo = memo.unwrap_or_default();
+        require!(memo.len() <= MAX_MEMO_LEN, CreditsError::MemoTooLong);
+        let sender = ctx.accounts.sender.key();
+        let recipient = ctx.accounts.recipient.key();
+        require!(sender != recipient, CreditsError::SelfGift);
+
+        let sender_credit = &mut ctx.accounts.sender_credit;
+        require!(sender_credit.user == sender, CreditsError::InvalidOwner);
+        require!(
+            sender_credit.credits >= amount,
+            CreditsError::InsufficientCredits
+        );
+        sender_credit.credits = sender_credit
+            .credits
+            .checked_sub(amount)
+            .ok_or(CreditsError::Overflow)?;
+        sender_credit.last_active_at = Clock::get()?.unix_timestamp;
+
+        let recipient_credit = &mut ctx.accounts.recipient_credit;
+        recipient_credit.user = recipient;
+        recipient_credit.credits = recipient_credit
+            .credits
+            .checked_add(amount)
+            .ok_or(CreditsError::Overflow)?;
+        recipient_credit.bump = ctx.bumps.recipient_credit;
+
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::GiftSent,
+            sender,
+            amount,
+        )?;
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::GiftReceived,
+            recipient,
+            amount,
+        )?;
+
+        emit!(Gifted {
+            sender,
+            recipient,
+            amount,
+            memo,
+            sender_balance: ctx.accounts.sender_credit.credits,
+            recipient_balance: recipient_credit.credits,
+        });
+        emit_balance_changed(
+            sender,
+            BalanceCategory::GiftSent,
+            amount,
+            ctx.accounts.sender_credit.credits,
+        )?;
+        emit_balance_changed(
+            recipient,
+            BalanceCategory::GiftReceived,
+            amount,
+            ctx.accounts.recipient_credit.credits,
+        )?;
+        Ok(())
+    }
+
+    // Spender maps a salted external ID hash to the user's credit account.
+    pub fn link_external_id(
+        ctx: Context<LinkExternalId>,
+        id_hash: [u8; 32],
+        user: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.spender.key() == ctx.accounts.config.spender,
+            CreditsError::Unauthorized
+        );
+        let link = &mut ctx.accounts.external_id;
+        link.config = ctx.accounts.config.key();
+        link.id_hash = id_hash;
+        link.user = user;
+        link.user_credit = Pubkey::find_program_address(&[b"credit", user.as_ref()], &crate::ID).0;
+        link.updated_at = Clock::get()?.unix_timestamp;
+        link.bump = ctx.bumps.external_id;
+
+        emit!(ExternalIdLinked {
+            config: link.config,
+            id_hash,
+            user,
+        });
+        Ok(())
+    }
+
+    // Spender removes an external ID mapping and reclaims its rent.
+    pub fn unlink_external_id(ctx: Context<UnlinkExternalId>) -> Result<()> {
+        require!(
+            ctx.accounts.spender.key() == ctx.accounts.config.spender,
+            CreditsError::Unauthorized
+        );
+        Ok(())
+    }
+
+    // Publish a Merkle root of (index, user, amount) promo credit grants.
+    pub fn create_airdrop(
+        ctx: Context<CreateAirdrop>,
+        airdrop_id: u32,
+        root: [u8; 32],
+        total_budget: u64,
+    ) -> Result<()> {
+        require!(total_budget > 0, CreditsError::InvalidAmount);
+        let airdrop = &mut ctx.accounts.airdrop;
+        airdrop.config = ctx.accounts.config.key();
+        airdrop.airdrop_id = airdrop_id;
+        airdrop.root = root;
+        airdrop.total_budget = total_budget;
+        airdrop.claimed = 0;
+        airdrop.bump = ctx.bumps.airdrop;
+
+        emit!(AirdropCreated {
+            config: airdrop.config,
+            airdrop_id,
+            root,
+            total_budget,
+        });
+        Ok(())
+    }
+
+    // User claims their airdrop leaf once; the receipt PDA prevents replays.
+    pub fn claim_airdrop(
+        ctx: Context<ClaimAirdrop>,
+        _airdrop_id: u32,
+        index: u32,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require_open(&ctx.accounts.global, &ctx.accounts.config)?;
+        require!(amount > 0, CreditsError::InvalidAmount);
+        require!(
+            proof.len() <= MAX_MERKLE_PROOF_LEN,
+            CreditsError::InvalidMerkleProof
+        );
+        let user = ctx.accounts.user.key();
+        let airdrop = &mut ctx.accounts.airdrop;
+        let leaf = hashv(&[
+            &[0u8],
+            &index.to_le_bytes(),
+            user.as_ref(),
+            &amount.to_le_bytes(),
+        ])
+        .to_bytes();
+        require!(
+            verify_merkle_proof(&proof, airdrop.root, leaf),
+            CreditsError::InvalidMerkleProof
+        );
+        let claimed = airdrop
+            .claimed
+            .checked_add(amount)
+            .ok_or(CreditsError::Overflow)?;
+        require!(
+            claimed <= airdrop.total_budget,
+            CreditsError::AirdropBudgetExceeded
+        );
+        airdrop.claimed = claimed;
+
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.airdrop = airdrop.key();
+        receipt.index = index;
+        receipt.user = user;
+        receipt.amount = amount;
+        receipt.bump = ctx.bumps.receipt;
+
+        let user_credit = &mut ctx.accounts.user_credit;
+        user_credit.user = user;
+        user_credit.credits = user_credit
+            .credits
+            .checked_add(amount)
+            .ok_or(CreditsError::Overflow)?;
+        user_credit.last_active_at = Clock::get()?.unix_timestamp;
+        user_credit.bump = ctx.bumps.user_credit;
+
+        let config = &mut ctx.accounts.config;
+        config.total_credits_issued = config
+            .total_credits_issued
+            .checked_add(amount)
+            .ok_or(CreditsError::Overflow)?;
+
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::Airdrop,
+            user,
+            amount,
+        )?;
+
+        emit!(AirdropClaimed {
+            airdrop: ctx.accounts.airdrop.key(),
+            user,
+            index,
+            amount,
+            new_balance: user_credit.credits,
+        });
+        emit_balance_changed(user, BalanceCategory::Airdrop, amount, user_credit.credits)?;
+        Ok(())
+    }
+
+    // Admin/spender-only debit of user credits.
+    pub fn consume_credit(ctx: Context<ConsumeCredit>, amount: u64) -> Result<()> {
+        require!(
+            !is_dormant(&ctx.accounts.config, &ctx.accounts.user_credit)?,
+            CreditsError::AcknowledgmentRequired
+        );
+        debit_credits(
+            &ctx.accounts.global,
+            &mut ctx.accounts.config,
+            ctx.accounts.spender.key(),
+            ctx.accounts.co_spender.as_ref().map(|signer| signer.key()),
+            ctx.accounts.user.key(),
+            &mut ctx.accounts.user_credit,
+            amount,
+        )?;
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::Consumed,
+            ctx.accounts.user.key(),
+            amount,
+        )
+    }
+
+    // Create a team pool; its shared balance is the credit account of the org PDA,
+    // so pay_for with the org as beneficiary tops it up.
+    pub fn create_org(ctx: Context<CreateOrg>, org_id: u32) -> Result<()> {
+        let org = &mut ctx.accounts.org;
+        org.config = ctx.accounts.config.key();
+        org.owner = ctx.accounts.owner.key();
+        org.org_id = org_id;
+        org.member_count = 0;
+        org.members = [Pubkey::default(); MAX_ORG_MEMBERS];
+        org.bump = ctx.bumps.org;
+
+        let org_credit = &mut ctx.accounts.org_credit;
+        org_credit.user = org.key();
+        org_credit.last_active_at = Clock::get()?.unix_timestamp;
+        org_credit.bump = ctx.bumps.org_credit;
+
+        emit!(OrgCreated {
+            config: org.config,
+            org: org.key(),
+            owner: org.owner,
+            org_id,
+        });
+        Ok(())
+    }
+
+    // Org owner grants a wallet the right to spend from the pool.
+    pub fn add_org_member(ctx: Context<UpdateOrg>, member: Pubkey) -> Result<()> {
+        let org = &mut ctx.accounts.org;
+        let count = usize::from(org.member_count);
+        require!(
+            !org.members[..count].contains(&member),
+            CreditsError::OrgMemberExists
+        );
+        require!(count < MAX_ORG_MEMBERS, CreditsError::OrgFull);
+        org.members[count] = member;
+        org.member_count += 1;
+
+        emit!(OrgMemberUpdated {
+            org: org.key(),
+            member,
+            added: true,
+        });
+        Ok(())
+    }
+
+    // Org owner revokes a member; the last member is swapped into the freed slot.
+    pub fn remove_org_member(ctx: Context<UpdateOrg>, member: Pubkey) -> Result<()> {
+        let org = &mut ctx.accounts.org;
+        let count = usize::from(org.member_count);
+        let index = org.members[..count]
+            .iter()
+            .position(|existing| *existing == member)
+            .ok_or(CreditsError::NotOrgMember)?;
+        org.members[index] = org.members[count - 1];
+        org.members[count - 1] = Pubkey::default();
+        org.member_count -= 1;
+
+        emit!(OrgMemberUpdated {
+            org: org.key(),
+            member,
+            added: false,
+        });
+        Ok(())
+    }
+
+    // Org owner marks the pool active; the org PDA can never sign acknowledge_spend,
+    // so this is how an idle pool is lifted out of dormancy.
+    pub fn touch_org(ctx: Context<TouchOrg>) -> Result<()> {
+        ctx.accounts.org_credit.last_active_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    // Spender debits the org pool on behalf of one of its members.
+    pub fn consume_org_credit(ctx: Context<ConsumeOrgCredit>, amount: u64) -> Result<()> {
+        let org = &ctx.accounts.org;
+        let member = ctx.accounts.member.key();
+        require!(
+            org.members[..usize::from(org.member_count)].contains(&member),
+            CreditsError::NotOrgMember
+        );
+        require!(
+            !is_dormant(&ctx.accounts.config, &ctx.accounts.org_credit)?,
+            CreditsError::AcknowledgmentRequired
+        );
+        debit_credits(
+            &ctx.accounts.global,
+            &mut ctx.accounts.config,
+            ctx.accounts.spender.key(),
+            ctx.accounts.co_spender.as_ref().map(|signer| signer.key()),
+            ctx.accounts.org.key(),
+            &mut ctx.accounts.org_credit,
+            amount,
+        )?;
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::Consumed,
+            ctx.accounts.org.key(),
+            amount,
+        )?;
+
+        emit!(OrgCreditUsed {
+            org: ctx.accounts.org.key(),
+            member,
+            amount,
+            new_balance: ctx.accounts.org_credit.credits,
+        });
+        Ok(())
+    }
+
+    // Spender commits a settlement manifest: a Merkle root of (user, cap) leaves.
+    pub fn commit_manifest(
+        ctx: Context<CommitManifest>,
+        manifest_id: u32,
+        root: [u8; 32],
+        total_cap: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.spender.key() == ctx.accounts.config.spender,
+            CreditsError::Unauthorized
+        );
+        require!(total_cap > 0, CreditsError::InvalidAmount);
+        let manifest = &mut ctx.accounts.manifest;
+        manifest.config = ctx.accounts.config.key();
+        manifest.manifest_id = manifest_id;
+        manifest.root = root;
+        manifest.total_cap = total_cap;
+        manifest.total_debited = 0;
+        manifest.bump = ctx.bumps.manifest;
+
+        emit!(ManifestCommitted {
+            config: manifest.config,
+            manifest_id,
+            root,
+            total_cap,
+        });
+        Ok(())
+    }
+
+    // Spender debit streamed against a manifest, bounded by the user's committed cap.
+    pub fn consume_with_manifest(
+        ctx: Context<ConsumeWithManifest>,
+        _manifest_id: u32,
+        amount: u64,
+        cap: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(
+            !is_dormant(&ctx.accounts.config, &ctx.accounts.user_credit)?,
+            CreditsError::AcknowledgmentRequired
+        );
+        require!(
+            proof.len() <= MAX_MERKLE_PROOF_LEN,
+            CreditsError::InvalidMerkleProof
+        );
+        let user = ctx.accounts.user.key();
+        let manifest = &mut ctx.accounts.manifest;
+        let leaf = hashv(&[&[0u8], user.as_ref(), &cap.to_le_bytes()]).to_bytes();
+        require!(
+            verify_merkle_proof(&proof, manifest.root, leaf),
+            CreditsError::InvalidMerkleProof
+        );
+        let total_debited = manifest
+            .total_debited
+            .checked_add(amount)
+            .ok_or(CreditsError::Overflow)?;
+        require!(
+            total_debited <= manifest.total_cap,
+            CreditsError::ManifestCapExceeded
+        );
+        manifest.total_debited = total_debited;
+
+        let usage = &mut ctx.accounts.usage;
+        let debited = usage
+            .debited
+            .checked_add(amount)
+            .ok_or(CreditsError::Overflow)?;
+        require!(debited <= cap, CreditsError::ManifestCapExceeded);
+        usage.manifest = manifest.key();
+        usage.user = user;
+        usage.debited = debited;
+        usage.bump = ctx.bumps.usage;
+
+        debit_credits(
+            &ctx.accounts.global,
+            &mut ctx.accounts.config,
+            ctx.accounts.spender.key(),
+            ctx.accounts.co_spender.as_ref().map(|signer| signer.key()),
+            user,
+            &mut ctx.accounts.user_credit,
+            amount,
+        )?;
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::Consumed,
+            user,
+            amount,
+        )
+    }
+
+    // Spender queues a debit against a dormant account for user acknowledgment.
+    pub fn request_spend(
+        ctx: Context<RequestSpend>,
+        spend_id: [u8; 32],
+        amount: u64,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(amount > 0, CreditsError::InvalidAmount);
+        require!(
+            ctx.accounts.spender.key() == config.spender,
+            CreditsError::Unauthorized
+        );
+        require!(
+            is_dormant(config, &ctx.accounts.user_credit)?,
+            CreditsError::AccountNotDormant
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let pending_spend = &mut ctx.accounts.pending_spend;
+        pending_spend.config = config.key();
+        pending_spend.user = ctx.accounts.user.key();
+        pending_spend.spender = ctx.accounts.spender.key();
+        pending_spend.spend_id = spend_id;
+        pending_spend.amount = amount;
+        pending_spend.requested_at = now;
+        pending_spend.confirm_after = now
+            .checked_add(config.pending_confirm_delay_secs)
+            .ok_or(CreditsError::Overflow)?;
+        pending_spend.acknowledged = false;
+        pending_spend.bump = ctx.bumps.pending_spend;
+
+        emit!(SpendRequested {
+            user: pending_spend.user,
+            spend_id,
+            amount,
+            confirm_after: pending_spend.confirm_after,
+        });
+        Ok(())
+    }
+
+    // User approves a pending spend, which also ends the account's dormancy.
+    pub fn acknowledge_spend(ctx: Context<AcknowledgeSpend>, _spend_id: [u8; 32]) -> Result<()> {
+        require_cpi_guard(&ctx.accounts.config, ctx.accounts.instructions.as_ref())?;
+        ctx.accounts.pending_spend.acknowledged = true;
+        ctx.accounts.user_credit.last_active_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    // User rejects a pending spend; rent returns to the spender.
+    pub fn reject_spend(ctx: Context<RejectSpend>, spend_id: [u8; 32]) -> Result<()> {
+        emit!(SpendRejected {
+            user: ctx.accounts.user.key(),
+            spend_id,
+            amount: ctx.accounts.pending_spend.amount,
+        });
+        Ok(())
+    }
+
+    // Spender executes a pending spend once acknowledged or past its confirm delay.
+    pub fn confirm_spend(ctx: Context<ConfirmSpend>, _spend_id: [u8; 32]) -> Result<()> {
+        let pending_spend = &ctx.accounts.pending_spend;
+        require!(
+            pending_spend.acknowledged
+                || Clock::get()?.unix_timestamp >= pending_spend.confirm_after,
+            CreditsError::SpendNotConfirmable
+        );
+        let amount = pending_spend.amount;
+
+        debit_credits(
+            &ctx.accounts.global,
+            &mut ctx.accounts.config,
+            ctx.accounts.spender.key(),
+            ctx.accounts.co_spender.as_ref().map(|signer| signer.key()),
+            ctx.accounts.user.key(),
+            &mut ctx.accounts.user_credit,
+            amount,
+        )?;
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::Consumed,
+            ctx.accounts.user.key(),
+            amount,
+        )
+    }
+
+    // User pre-authorizes the config's current spender to debit up to max_cost credits
+    // for one job.
+    pub fn authorize_job(
+        ctx: Context<AuthorizeJob>,
+        job_id: [u8; 32],
+        max_cost: u64,
+    ) -> Result<()> {
+        require!(max_cost > 0, CreditsError::InvalidAmount);
+        require_cpi_guard(&ctx.accounts.config, ctx.accounts.instructions.as_ref())?;
+        let authorization = &mut ctx.accounts.authorization;
+        authorization.config = ctx.accounts.config.key();
+        authorization.spender = ctx.accounts.config.spender;
+        authorization.user = ctx.accounts.user.key();
+        authorization.job_id = job_id;
+        authorization.max_cost = max_cost;
+        authorization.consumed = 0;
+        authorization.bump = ctx.bumps.authorization;
+
+        emit!(JobAuthorized {
+            config: authorization.config,
+            spender: authorization.spender,
+            user: ctx.accounts.user.key(),
+            job_id,
+            max_cost,
+        });
+        Ok(())
+    }
+
+    // User withdraws a job authorization and reclaims its rent.
+    pub fn revoke_job_authorization(
+        _ctx: Context<RevokeJobAuthorization>,
+        _job_id: [u8; 32],
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    // Spender-only debit bounded by the user's authorization for the job.
+    pub fn consume_job_credit(
+        ctx: Context<ConsumeJobCredit>,
+        job_id: [u8; 32],
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            !is_dormant(&ctx.accounts.config, &ctx.accounts.user_credit)?,
+            CreditsError::AcknowledgmentRequired
+        );
+        let authorization = &mut ctx.accounts.authorization;
+        let consumed = authorization
+            .consumed
+            .checked_add(amount)
+            .ok_or(CreditsError::Overflow)?;
+        require!(
+            consumed <= authorization.max_cost,
+            CreditsError::AuthorizationExceeded
+        );
+        authorization.consumed = consumed;
+
+        debit_authorized_credits(
+            &ctx.accounts.global,
+            &mut ctx.accounts.config,
+            ctx.accounts.spender.key(),
+            ctx.accounts.co_spender.as_ref().map(|signer| signer.key()),
+            ctx.accounts.user.key(),
+            &mut ctx.accounts.user_credit,
+            amount,
+        )?;
+        record_journal(
+            &ctx.accounts.config,
+            ctx.accounts.journal.as_mut(),
+            JournalKind::Consumed,
+            ctx.accounts.user.key(),
+            amount,
+        )?;
+
+        emit!(JobCreditUsed {
+            user: ctx.accounts.user.key(),
+            job_id,
+            amount,
+            consumed,
+            max_cost: authorization.max_cost,
+        });
+        Ok(())
+    }
+}
+
+// Convert a token amount with `decimals` into 6-decimal USD base units.
+fn to_usd_base_units(amount: u64, decimals: u8) -> Result<u64> {
+    let value = if decimals >= USD_DECIMALS {
+        let scale = 10u128
+            .checked_pow(u32::from(decimals - USD_DECIMALS))
+            .ok_or(CreditsError::Overflow)?;
+        u128::from(amount) / scale
+    } else {
+        let scale = 10u128.pow(u32::from(USD_DECIMALS - decimals));
+        u128::from(amount)
+            .checked_mul(scale)
+            .ok_or(CreditsError::Overflow)?
+    };
+    u64::try_from(value).map_err(|_| error!(CreditsError::Overflow))
+}
+
+// Convert 6-decimal USD base units into a token amount with `decimals`, rounding up.
+fn from_usd_base_units(usd_base_units: u64, decimals: u8) -> Result<u64> {
+    let value = if decimals >= USD_DECIMALS {
+        let scale = 10u128
+            .checked_pow(u32::from(decimals - USD_DECIMALS))
+            .ok_or(CreditsError::Overflow)?;
+        u128::from(usd_base_units)
+            .checked_mul(scale)
+            .ok_or(CreditsError::Overflow)?
+    } else {
+        let scale = 10u128.pow(u32::from(USD_DECIMALS - decimals));
+        u128::from(usd_base_units).div_ceil(scale)
+    };
+    u64::try_from(value).map_err(|_| error!(CreditsError::Overflow))
+}
+
+// Solana Pay reference keys passed as trailing read-only remaining accounts.
+fn payment_references(remaining_accounts: &[AccountInfo]) -> Result<Vec<Pubkey>> {
+    require!(
+        remaining_accounts.len() <= MAX_PAYMENT_REFERENCES,
+        CreditsError::TooManyReferences
+    );
+    remaining_accounts
+        .iter()
+        .map(|reference| {
+            require!(!reference.is_writable, CreditsError::InvalidReference);
+            Ok(reference.key())
+        })
+        .collect()
+}
+
+// Enforce the config's per-transaction purchase bounds (max = 0 means uncapped).
+fn require_purchase_limits(config: &Config, usd_base_units: u64) -> Result<()> {
+    require!(
+        usd_base_units >= config.min_purchase_base_units,
+        CreditsError::PurchaseBelowMinimum
+    );
+    require!(
+        config.max_purchase_base_units == 0 || usd_base_units <= config.max_purchase_base_units,
+        CreditsError::PurchaseAboveMaximum
+    );
+    Ok(())
+}
+
+// Count credits issued by a purchase path; only the `sold` base credits move the pricing
+// curve. Returns whether the sale moved the spot price.
+fn record_sale(config: &mut Config, credits: u64, sold_credits: u64) -> Result<bool> {
+    let unit_before = current_credit_unit(config)?;
+    config.total_credits_issued = config
+        .total_credits_issued
+        .checked_add(credits)
+        .ok_or(CreditsError::Overflow)?;
+    config.credits_sold = config
+        .credits_sold
+        .checked_add(sold_credits)
+        .ok_or(CreditsError::Overflow)?;
+    Ok(current_credit_unit(config)? != unit_before)
+}
+
+// Credits a purchase path adds: `sold_credits` of them were paid for at the curve price,
+// the rest (bonuses, prepaid cards) are issued without moving it.
+struct CreditedPurchase {
+    kind: JournalKind,
+    user: Pubkey,
+    credits: u64,
+    sold_credits: u64,
+}
+
+// Credit a completed purchase: balance, sale counters, price feed, journal and
+// BalanceChanged. Shared by every purchase path; callers emit their own event afterwards.
+fn credit_purchase(
+    config: &mut Config,
+    journal: Option<&mut Account<Journal>>,
+    price_feed: Option<&mut Account<PriceFeed>>,
+    user_credit: &mut UserCredit,
+    purchase: CreditedPurchase,
+) -> Result<()> {
+    let CreditedPurchase {
+        kind,
+        user,
+        credits,
+        sold_credits,
+    } = purchase;
+    user_credit.user = user;
+    user_credit.credits = user_credit
+        .credits
+        .checked_add(credits)
+        .ok_or(CreditsError::Overflow)?;
+    if record_sale(config, credits, sold_credits)? {
+        let price_feed = price_feed.ok_or(CreditsError::MissingPriceFeed)?;
+        sync_price_feed(price_feed, config)?;
+    }
+    record_journal(config, journal, kind, user, credits)?;
+    emit_balance_changed(
+        user,
+        BalanceCategory::Purchase,
+        credits,
+        user_credit.credits,
+    )
+}
+
+// Spot price of one credit in USD base units for the next credit sold.
+fn current_credit_unit(config: &Config) -> Result<u64> {
+    Ok(curve_step(config, config.credits_sold)?.0)
+}
+
+// Price of one credit once `sold` credits have sold, and how many more sell at that price
+// before the next step (None once the price can no longer move). In curve mode the price
+// rises curve_step_bps of the base for every curve_step_credits sold, up to curve_max_unit.
+fn curve_step(config: &Config, sold: u64) -> Result<(u64, Option<u64>)> {
+    if config.pricing_mode == PricingMode::Flat {
+        return Ok((config.credit_unit, None));
+    }
+    let steps = sold / config.curve_step_credits;
+    let increase = u128::from(config.curve_base_unit)
+        .checked_mul(u128::from(config.curve_step_bps))
+        .and_then(|value| value.checked_mul(u128::from(steps)))
+        .ok_or(CreditsError::Overflow)?
+        / u128::from(BPS_DENOMINATOR);
+    let unit = u128::from(config.curve_base_unit).saturating_add(increase);
+    let unit = unit.min(u128::from(config.curve_max_unit)) as u64;
+    if config.curve_step_bps == 0 || unit >= config.curve_max_unit {
+        return Ok((unit, None));
+    }
+    let left_in_step = config.curve_step_credits - sold % config.curve_step_credits;
+    Ok((unit, Some(left_in_step)))
+}
+
+// Whole credits `usd_base_units` buys and the USD left over, integrated across every
+// curve step the purchase spans rather than priced at the spot step.
+fn credits_for_usd(config: &Config, usd_base_units: u64) -> Result<(u64, u64)> {
+    let mut usd = usd_base_units;
+    let mut credits = 0u64;
+    let mut sold = config.credits_sold;
+    for _ in 0..MAX_CURVE_STEPS_PER_PURCHASE {
+        let (unit, left_in_step) = curve_step(config, sold)?;
+        let step_cost = left_in_step.map(|left| u128::from(left) * u128::from(unit));
+        match (left_in_step, step_cost) {
+            (Some(left), Some(cost)) if u128::from(usd) >= cost => {
+                usd -= cost as u64;
+                credits = credits.checked_add(left).ok_or(CreditsError::Overflow)?;
+                sold = sold.checked_add(left).ok_or(CreditsError::Overflow)?;
+            }
+            _ => {
+                let bought = credits
+                    .checked_add(usd / unit)
+                    .ok_or(CreditsError::Overflow)?;
+                return Ok((bought, usd % unit));
+            }
+        }
+    }
+    err!(CreditsError::PurchaseSpansTooManySteps)
+}
+
+// USD base units that `credits` more credits cost, integrated across the curve steps.
+fn usd_for_credits(config: &Config, credits: u64) -> Result<u64> {
+    let mut usd = 0u64;
+    let mut remaining = credits;
+    let mut sold = config.credits_sold;
+    for _ in 0..MAX_CURVE_STEPS_PER_PURCHASE {
+        let (unit, left_in_step) = curve_step(config, sold)?;
+        let taken = left_in_step.map_or(remaining, |left| left.min(remaining));
+        let cost = taken.checked_mul(unit).ok_or(CreditsError::Overflow)?;
+        usd = usd.checked_add(cost).ok_or(CreditsError::Overflow)?;
+        remaining -= taken;
+        if remaining == 0 {
+            return Ok(usd);
+        }
+        sold = sold.checked_add(taken).ok_or(CreditsError::Overflow)?;
+    }
+    err!(CreditsError::PurchaseSpansTooManySteps)
+}
+
+// Optional offers a stablecoin purchase may stack on its base credits.
+struct PurchaseOffers<'a, 'info> {
+    user: Pubkey,
+    price_tiers: Option<&'a Account<'info, PriceTiers>>,
+    voucher: Option<&'a Account<'info, Voucher>>,
+    voucher_code: Option<&'a str>,
+    campaign: Option<&'a Account<'info, Campaign>>,
+    membership_token: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    membership_metadata: Option<&'a UncheckedAccount<'info>>,
+    holder_tiers: Option<&'a Account<'info, HolderTiers>>,
+    holder_token: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+}
+
+struct PurchaseCredits {
+    base_credits: u64,
+    bonus_credits: u64,
+    voucher_bonus: u64,
+    variant_id: Option<u16>,
+    remainder_base_units: u64,
+}
+
+// Price a stablecoin purchase of `usd_base_units` that actually landed, plus the buyer's
+// carried remainder. pay_stable, pay_for and quote_credits all go through here so
+// quotes match.
+fn price_purchase(
+    config: &Config,
+    usd_base_units: u64,
+    remainder_base_units: u64,
+    offers: &PurchaseOffers,
+) -> Result<PurchaseCredits> {
+    let total = usd_base_units
+        .checked_add(remainder_base_units)
+        .ok_or(CreditsError::Overflow)?;
+    let (base_credits, remainder_base_units) = credits_for_usd(config, total)?;
+    require!(base_credits > 0, CreditsError::BelowMinimum);
+
+    let tier_bonus = match offers.price_tiers {
+        Some(price_tiers) => price_tiers.bonus_credits(usd_base_units, base_credits)?,
+        None => 0,
+    };
+    let voucher_bonus = match offers.voucher {
+        Some(voucher) => {
+            let code = offers
+                .voucher_code
+                .ok_or(CreditsError::InvalidVoucherCode)?;
+            voucher.bonus_credits(code, base_credits)?
+        }
+        None => 0,
+    };
+    let campaign_bonus = match offers.campaign {
+        Some(campaign) => campaign.bonus_credits(base_credits)?,
+        None => 0,
+    };
+    let membership_bonus = match (offers.membership_token, offers.membership_metadata) {
+        (Some(token), Some(metadata)) => {
+            require_membership(config, &offers.user, token, metadata)?;
+            discount_credits(base_credits, config.membership_discount_bps)?
+        }
+        (None, None) => 0,
+        _ => return err!(CreditsError::InvalidMembership),
+    };
+    let holder_bonus = match (offers.holder_tiers, offers.holder_token) {
+        (Some(holder_tiers), Some(holder_token)) => {
+            require!(
+                holder_token.mint == holder_tiers.mint && holder_token.owner == offers.user,
+                CreditsError::InvalidHolderToken
+            );
+            holder_tiers.bonus_credits(holder_token.amount, base_credits)?
+        }
+        (None, None) => 0,
+        _ => return err!(CreditsError::InvalidHolderToken),
+    };
+    let variant = assigned_variant(config, &offers.user);
+    let variant_bonus = match variant {
+        Some(variant) => variant.bonus_credits(base_credits)?,
+        None => 0,
+    };
+    let bonus_credits = tier_bonus
+        .checked_add(voucher_bonus)
+        .and_then(|bonus| bonus.checked_add(variant_bonus))
+        .and_then(|bonus| bonus.checked_add(campaign_bonus))
+        .and_then(|bonus| bonus.checked_add(membership_bonus))
+        .and_then(|bonus| bonus.checked_add(holder_bonus))
+        .ok_or(CreditsError::Overflow)?;
+    let bonus_credits = match config.max_bonus_bps {
+        0 => bonus_credits,
+        max_bonus_bps => bonus_credits.min(bps_of(base_credits, max_bonus_bps)?),
+    };
+
+    Ok(PurchaseCredits {
+        base_credits,
+        bonus_credits,
+        voucher_bonus,
+        variant_id: variant.map(|variant| variant.variant_id),
+        remainder_base_units,
+    })
+}
+
+// `bps` basis points of `amount`, rounded down.
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let value = u128::from(amount)
+        .checked_mul(u128::from(bps))
+        .ok_or(CreditsError::Overflow)?
+        / u128::from(BPS_DENOMINATOR);
+    u64::try_from(value).map_err(|_| error!(CreditsError::Overflow))
+}
+
+// A/B variant for `user`, bucketed by a hash of their key so buyers cannot pick an arm.
+fn assigned_variant<'a>(config: &'a Config, user: &Pubkey) -> Option<&'a PricingVariant> {
+    let variants = &config.pricing_variants[..usize::from(config.variant_count)];
+    if variants.is_empty() {
+        return None;
+    }
+    let digest = hashv(&[b"pricing_variant", user.as_ref()]).to_bytes();
+    let bucket = usize::from(u16::from_le_bytes([digest[0], digest[1]])) % variants.len();
+    variants.get(bucket)
+}
+
+// Whole credits bought by a payment; the sub-credit dust is carried on the account
+// and converts once it adds up to a full credit.
+fn credits_with_remainder(
+    config: &Config,
+    user_credit: &mut UserCredit,
+    usd_base_units: u64,
+) -> Result<u64> {
+    let total = usd_base_units
+        .checked_add(user_credit.remainder_base_units)
+        .ok_or(CreditsError::Overflow)?;
+    let (credits, remainder_base_units) = credits_for_usd(config, total)?;
+    user_credit.remainder_base_units = remainder_base_units;
+    Ok(credits)
+}
+
+// Largest token amount not above `amount` that buys a whole number of credits.
+fn whole_credit_charge(config: &Config, amount: u64, decimals: u8) -> Result<u64> {
+    let (credits, _) = credits_for_usd(config, to_usd_base_units(amount, decimals)?)?;
+    require!(credits > 0, CreditsError::BelowMinimum);
+    from_usd_base_units(usd_for_credits(config, credits)?, decimals)
+}
+
+// Check that the payment accounts match the accepted mint, payer and config vault.
+fn require_stable_accounts(
+    config: &Account<Config>,
+    accepted_mint: &AcceptedMint,
+    mint: &InterfaceAccount<Mint>,
+    user_token: &InterfaceAccount<TokenAccount>,
+    vault_token: &InterfaceAccount<TokenAccount>,
+    user: Pubkey,
+    region_vault: Option<&Account<RegionVault>>,
+) -> Result<()> {
+    require!(mint.key() == accepted_mint.mint, CreditsError::InvalidMint);
+    require!(
+        user_token.mint == accepted_mint.mint,
+        CreditsError::InvalidMint
+    );
+    require!(
+        vault_token.mint == accepted_mint.mint,
+        CreditsError::InvalidMint
+    );
+    require!(user_token.owner == user, CreditsError::InvalidOwner);
+    require!(
+        vault_token.owner == config.key(),
+        CreditsError::InvalidOwner
+    );
+    if config.region_count > 0 {
+        let region_vault = region_vault.ok_or(CreditsError::MissingRegionVault)?;
+        require!(
+            region_vault.vault_token == vault_token.key(),
+            CreditsError::InvalidRegionVault
+        );
+    } else {
+        require!(
+            vault_token.key() == accepted_mint.vault,
+            CreditsError::InvalidVault
+        );
+    }
+    Ok(())
+}
+
+// Enforce the CPI and depeg guards shared by stablecoin payment paths.
+fn require_payment_guards(
+    config: &Config,
+    price_update: Option<&Account<PriceUpdateV2>>,
+    instructions: Option<&UncheckedAccount>,
+) -> Result<()> {
+    require_cpi_guard(config, instructions)?;
+
+    if config.depeg_band_bps > 0 {
+        require!(!config.depeg_tripped, CreditsError::DepegDetected);
+        let price_update = price_update.ok_or(CreditsError::MissingPriceUpdate)?;
+        let deviation_bps = usdc_deviation_bps(config, price_update)?;
+        require!(
+            deviation_bps <= u64::from(config.depeg_band_bps),
+            CreditsError::DepegDetected
+        );
+    }
+    Ok(())
+}
+
+// Source of a stablecoin payment; signer_seeds is set when a PDA delegate signs.
+struct StablePayer<'a, 'info> {
+    token_program: &'a Interface<'info, TokenInterface>,
+    mint: &'a InterfaceAccount<'info, Mint>,
+    from: &'a InterfaceAccount<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &'a [&'a [&'a [u8]]],
+}
+
+// Transfer `amount` to `to` and return what actually landed there.
+fn transfer_stable<'info>(
+    payer: &StablePayer<'_, 'info>,
+    to: &mut InterfaceAccount<'info, TokenAccount>,
+    amount: u64,
+) -> Result<u64> {
+    let balance_before = to.amount;
+    let cpi_accounts = TransferChecked {
+        from: payer.from.to_account_info(),
+        mint: payer.mint.to_account_info(),
+        to: to.to_account_info(),
+        authority: payer.authority.clone(),
+    };
+    let cpi_program = payer.token_program.to_account_info();
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, payer.signer_seeds),
+        amount,
+        payer.mint.decimals,
+    )?;
+    to.reload()?;
+    let received = to
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(CreditsError::Overflow)?;
+    Ok(received)
+}
+
+// Partner fee owed to the platform on a purchase of `amount`.
+fn platform_fee_for(config: &Config, amount: u64) -> Result<u64> {
+    Ok(amount
+        .checked_mul(u64::from(config.platform_fee_bps))
+        .ok_or(CreditsError::Overflow)?
+        / BPS_DENOMINATOR)
+}
+
+// Token-2022 transfer fee withheld from `amount` this epoch; zero for mints without one.
+fn transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    if amount == 0 {
+        return Ok(0);
+    }
+    let info = mint.to_account_info();
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    let Ok(fee_config) = state.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+    fee_config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or_else(|| error!(CreditsError::Overflow))
+}
+
+// Route the partner fee to the platform config's ATA for the paying mint;
+// returns (fee charged, fee received).
+fn collect_platform_fee<'info>(
+    config: &Account<'info, Config>,
+    payer: &StablePayer<'_, 'info>,
+    platform_vault: Option<&mut InterfaceAccount<'info, TokenAccount>>,
+    amount: u64,
+) -> Result<(u64, u64)> {
+    let platform_fee = platform_fee_for(config, amount)?;
+    if platform_fee == 0 {
+        return Ok((0, 0));
+    }
+    let platform_vault = platform_vault.ok_or(CreditsError::InvalidPlatformVault)?;
+    let expected_vault = get_associated_token_address_with_program_id(
+        &config.platform_config,
+        &payer.mint.key(),
+        &payer.token_program.key(),
+    );
+    require!(
+        platform_vault.key() == expected_vault,
+        CreditsError::InvalidPlatformVault
+    );
+    let fee_received = transfer_stable(payer, platform_vault, platform_fee)?;
+
+    emit!(PlatformFeePaid {
+        config: config.key(),
+        platform_vault: platform_vault.key(),
+        amount_base_units: fee_received,
+    });
+    Ok((platform_fee, fee_received))
+}
+
+// Pull `amount` from the payer, routing the partner fee to the platform vault and the
+// rest to the config vault; returns what actually landed across both.
+fn receive_stable_payment<'info>(
+    config: &Account<'info, Config>,
+    payer: &StablePayer<'_, 'info>,
+    platform_vault: Option<&mut InterfaceAccount<'info, TokenAccount>>,
+    vault_token: &mut InterfaceAccount<'info, TokenAccount>,
+    amount: u64,
+) -> Result<u64> {
+    let (platform_fee, fee_received) = collect_platform_fee(config, payer, platform_vault, amount)?;
+    let vault_received = transfer_stable(payer, vault_token, amount - platform_fee)?;
+    fee_received
+        .checked_add(vault_received)
+        .ok_or_else(|| error!(CreditsError::Overflow))
+}
+
+// Apply the CPI guard to a user-signed instruction when the config has enabled it.
+fn require_cpi_guard(config: &Config, instructions: Option<&UncheckedAccount>) -> Result<()> {
+    if config.cpi_guard {
+        let instructions = instructions.ok_or(CreditsError::MissingInstructionsSysvar)?;
+        require_allowed_caller(config, instructions)?;
+    }
+    Ok(())
+}
+
+// Reject CPI invocations unless the top-level program is allow-listed.
+fn require_allowed_caller(config: &Config, instructions: &AccountInfo) -> Result<()> {
+    let current = instructions_sysvar::get_instruction_relative(0, instructions)?;
+    if current.program_id == crate::ID {
+        return Ok(());
+    }
+    require!(
+        current.program_id != Pubkey::default()
+            && config.cpi_allowed_callers.contains(&current.program_id),
+        CreditsError::CpiCallerNotAllowed
+    );
+    Ok(())
+}
+
+// Require the previous instruction to be an ed25519 verify of `message` by `signer`.
+// Offsets must point into that same instruction so the checked bytes are the ones verified.
+fn require_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let verify = instructions_sysvar::get_instruction_relative(-1, instructions)
+        .map_err(|_| error!(CreditsError::InvalidSignature))?;
+    require!(
+        verify.program_id == ed25519_program::ID,
+        CreditsError::InvalidSignature
+    );
+    let data = &verify.data;
+    // [num_signatures, padding, offsets (7 x u16), ...]
+    require!(
+        data.len() >= 16 && data[0] == 1,
+        CreditsError::InvalidSignature
+    );
+    let read_u16 = |at: usize| usize::from(u16::from_le_bytes([data[at], data[at + 1]]));
+    let public_key_offset = read_u16(6);
+    let message_offset = read_u16(10);
+    let message_size = read_u16(12);
+    require!(
+        read_u16(4) == usize::from(u16::MAX)
+            && read_u16(8) == usize::from(u16::MAX)
+            && read_u16(14) == usize::from(u16::MAX),
+        CreditsError::InvalidSignature
+    );
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(CreditsError::InvalidSignature)?;
+    let signed = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(CreditsError::InvalidSignature)?;
+    require!(
+        public_key == signer.as_ref() && signed == message,
+        CreditsError::InvalidSignature
+    );
+    Ok(())
+}
+
+// Copy pricing fields from the config into its price feed.
+fn sync_price_feed(price_feed: &mut PriceFeed, config: &Config) -> Result<()> {
+    price_feed.usdc_mint = config.usdc_mint;
+    price_feed.credit_unit = current_credit_unit(config)?;
+    price_feed.membership_discount_bps = config.membership_discount_bps;
+    price_feed.platform_fee_bps = config.platform_fee_bps;
+    price_feed.updated_slot = Clock::get()?.slot;
+
+    emit!(PricePublished {
+        config: price_feed.config,
+        credit_unit: price_feed.credit_unit,
+        membership_discount_bps: price_feed.membership_discount_bps,
+        platform_fee_bps: price_feed.platform_fee_bps,
+        slot: price_feed.updated_slot,
+    });
+    Ok(())
+}
+
+// Append an entry to the config's journal ring buffer when journaling is enabled.
+fn record_journal(
+    config: &Config,
+    journal: Option<&mut Account<Journal>>,
+    kind: JournalKind,
+    user: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    if !config.journal_enabled {
+        return Ok(());
+    }
+    let journal = journal.ok_or(CreditsError::MissingJournal)?;
+    let seq = journal.next_seq;
+    let index = (seq % JOURNAL_CAPACITY as u64) as usize;
+    journal.entries[index] = JournalEntry {
+        seq,
+        kind,
+        user,
+        amount,
+        slot: Clock::get()?.slot,
+    };
+    journal.next_seq = seq.checked_add(1).ok_or(CreditsError::Overflow)?;
+    Ok(())
+}
+
+// Extra credits equivalent to a price discount: d bps buys base / (1 - d) for the same payment.
+fn discount_credits(base_credits: u64, discount_bps: u16) -> Result<u64> {
+    let credits = u128::from(base_credits)
+        .checked_mul(u128::from(discount_bps))
+        .ok_or(CreditsError::Overflow)?
+        / u128::from(BPS_DENOMINATOR - u64::from(discount_bps));
+    u64::try_from(credits).map_err(|_| error!(CreditsError::Overflow))
+}
+
+// Require `user` to hold an NFT whose Metaplex metadata verifies the configured collection.
+fn require_membership(
+    config: &Config,
+    user: &Pubkey,
+    token: &InterfaceAccount<TokenAccount>,
+    metadata: &AccountInfo,
+) -> Result<()> {
+    require!(
+        config.membership_discount_bps > 0 && config.membership_collection != Pubkey::default(),
+        CreditsError::InvalidMembership
+    );
+    require!(
+        token.owner == *user && token.amount > 0,
+        CreditsError::InvalidMembership
+    );
+    let (expected, _) = Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            token.mint.as_ref(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    );
+    require!(
+        metadata.key() == expected && *metadata.owner == TOKEN_METADATA_PROGRAM_ID,
+        CreditsError::InvalidMembership
+    );
+    let data = metadata.try_borrow_data()?;
+    require!(
+        verified_collection(&data) == Some(config.membership_collection),
+        CreditsError::InvalidMembership
+    );
+    Ok(())
+}
+
+// Walk the Borsh layout of a Metaplex MetadataV1 account up to its collection field.
+fn verified_collection(data: &[u8]) -> Option<Pubkey> {
+    let mut cursor = data;
+    let mut take = |len: usize| -> Option<&[u8]> {
+        if cursor.len() < len {
+            return None;
+        }
+        let (head, tail) = cursor.split_at(len);
+        cursor = tail;
+        Some(head)
+    };
+    if take(1)?[0] != METADATA_V1_KEY {
+        return None;
+    }
+    // update_authority, mint
+    take(64)?;
+    // name, symbol, uri
+    for _ in 0..3 {
+        let len = u32::from_le_bytes(take(4)?.try_into().ok()?);
+        take(len as usize)?;
+    }
+    // seller_fee_basis_points
+    take(2)?;
+    // creators: Option<Vec<Creator { address, verified, share }>>
+    if take(1)?[0] == 1 {
+        let count = u32::from_le_bytes(take(4)?.try_into().ok()?);
+        take((count as usize).checked_mul(34)?)?;
+    }
+    // primary_sale_happened, is_mutable
+    take(2)?;
+    // edition_nonce, token_standard: Option<u8>
+    for _ in 0..2 {
+        if take(1)?[0] == 1 {
+            take(1)?;
+        }
+    }
+    // collection: Option<Collection { verified, key }>
+    if take(1)?[0] != 1 || take(1)?[0] != 1 {
+        return None;
+    }
+    Pubkey::try_from(take(32)?).ok()
+}
+
+// Verify a sorted-pair Merkle proof; interior nodes are hashed with a 0x01 prefix.
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        hashv(&[&[1u8], &left, &right]).to_bytes()
+    });
+    computed == root
+}
+
+// Shared entry guard for purchases and debits: global freeze, program version and sunset.
+fn require_open(global: &GlobalState, config: &Config) -> Result<()> {
+    require!(!global.frozen, CreditsError::GloballyFrozen);
+    require_current_version(config)?;
+    require_not_sunset(config)
+}
+
+// Reject purchases and debits until the config has been confirmed for this program version.
+fn require_current_version(config: &Config) -> Result<()> {
+    require!(
+        config.program_version == PROGRAM_VERSION,
+        CreditsError::VersionMismatch
+    );
+    Ok(())
+}
+
+// Reject purchases and debits once the config's sunset has taken effect.
+fn require_not_sunset(config: &Config) -> Result<()> {
+    require!(
+        config.sunset_at == 0 || Clock::get()?.unix_timestamp < config.sunset_at,
+        CreditsError::ConfigReadOnly
+    );
+    Ok(())
+}
+
+// Grow an account to the current layout, topping up rent from the payer; the new
+// trailing fields start zeroed.
+fn migrate_account<'info>(
+    payer: &Signer<'info>,
+    account: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    discriminator: &[u8],
+    new_len: usize,
+) -> Result<()> {
+    let info = account.to_account_info();
+    let old_len = info.data_len();
+    require!(
+        info.try_borrow_data()?.starts_with(discriminator),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    require!(old_len < new_len, CreditsError::AlreadyMigrated);
+
+    let rent = Rent::get()?;
+    let shortfall = rent
+        .minimum_balance(new_len)
+        .saturating_sub(info.lamports());
+    if shortfall > 0 {
+        let cpi_accounts = system_program::Transfer {
+            from: payer.to_account_info(),
+            to: info.clone(),
+        };
+        let cpi_program = system_program.to_account_info();
+        system_program::transfer(CpiContext::new(cpi_program, cpi_accounts), shortfall)?;
+    }
+    info.resize(new_len)?;
+    info.try_borrow_mut_data()?[old_len..].fill(0);
+    require!(
+        rent.is_exempt(info.lamports(), new_len),
+        CreditsError::NotRentExempt
+    );
+
+    emit!(AccountMigrated {
+        account: info.key(),
+        payer: payer.key(),
+        old_len: old_len as u64,
+        new_len: new_len as u64,
+    });
+    Ok(())
+}
+
+// Whether the config's dormancy guard applies to this account's next debit.
+fn is_dormant(config: &Config, user_credit: &UserCredit) -> Result<bool> {
+    if config.dormancy_secs == 0 {
+        return Ok(false);
+    }
+    let idle_secs = Clock::get()?
+        .unix_timestamp
+        .saturating_sub(user_credit.last_active_at);
+    Ok(idle_secs >= config.dormancy_secs)
+}
+
+// Wallet-facing summary emitted next to the specific event on every balance change.
+fn emit_balance_changed(
+    user: Pubkey,
+    category: BalanceCategory,
+    amount: u64,
+    new_balance: u64,
+) -> Result<()> {
+    let magnitude = i64::try_from(amount).map_err(|_| error!(CreditsError::Overflow))?;
+    let delta = if category.is_debit() {
+        -magnitude
+    } else {
+        magnitude
+    };
+    emit!(BalanceChanged {
+        user,
+        delta,
+        new_balance,
+        category,
+    });
+    Ok(())
+}
+
+// Shared spender debit used by consume_credit, consume_with_manifest, confirm_spend and
+// consume_org_credit; none carries a job Authorization, so all are refused while per-job
+// consent is required.
+fn debit_credits(
+    global: &GlobalState,
+    config: &mut Config,
+    spender: Pubkey,
+    co_spender: Option<Pubkey>,
+    user: Pubkey,
+    user_credit: &mut UserCredit,
+    amount: u64,
+) -> Result<()> {
+    require!(
+        !config.job_authorization_required,
+        CreditsError::JobAuthorizationRequired
+    );
+    debit_authorized_credits(
+        global,
+        config,
+        spender,
+        co_spender,
+        user,
+        user_credit,
+        amount,
+    )
+}
+
+// Spender debit without the per-job consent check; only consume_job_credit, which has
+// already charged the user's Authorization, may call this directly.
+fn debit_authorized_credits(
+    global: &GlobalState,
+    config: &mut Config,
+    spender: Pubkey,
+    co_spender: Option<Pubkey>,
+    user: Pubkey,
+    user_credit: &mut UserCredit,
+    amount: u64,
+) -> Result<()> {
+    require_open(global, config)?;
+    require!(amount > 0, CreditsError::InvalidAmount);
+    require!(spender == config.spender, CreditsError::Unauthorized);
+    if config.co_sign_threshold > 0 && amount >= config.co_sign_threshold {
+        require!(
+            co_spender == Some(config.co_spender) && config.co_spender != spender,
+            CreditsError::CoSignatureRequired
+        );
+    }
+    config.spender_last_active_slot = Clock::get()?.slot;
+    require!(user_credit.user == user, CreditsError::InvalidOwner);
+    require!(
+        user_credit.credits >= amount,
+        CreditsError::InsufficientCredits
+    );
+    // Spender debits never count as user activity, or they would lift the dormancy guard.
+    user_credit.credits = user_credit
+        .credits
+        .checked_sub(amount)
+        .ok_or(CreditsError::Overflow)?;
+    config.total_credits_consumed = config
+        .total_credits_consumed
+        .checked_add(amount)
+        .ok_or(CreditsError::Overflow)?;
+
+    emit!(CreditUsed {
+        user,
+        amount,
+        new_balance: user_credit.credits,
+    });
+    emit_balance_changed(
+        user,
+        BalanceCategory::Consumption,
+        amount,
+        user_credit.credits,
+    )
+}
+
+#[derive(Accounts)]
+pub struct InitializeRegistry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Registry::INIT_SPACE,
+        seeds = [b"registry"],
+        bump
+    )]
+    pub registry: Account<'info, Registry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGlobal<'info> {
+    #[account(mut)]
+    pub super_authority: Signer<'info>,
+    #[account(
+        init,
+        payer = super_authority,
+        space = 8 + GlobalState::INIT_SPACE,
+        seeds = [b"global"],
+        bump
+    )]
+    pub global: Account<'info, GlobalState>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::KangklipCredits>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(super_authority.key())
+            @ CreditsError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGlobalFreeze<'info> {
+    pub super_authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = super_authority,
+        seeds = [b"global"],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalState>,
+}
+
+#[derive(Accounts)]
+pub struct SetPartnerFee<'info> {
+    pub super_authority: Signer<'info>,
+    #[account(
+        has_one = super_authority,
+        seeds = [b"global"],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [b"config", partner_config.authority.as_ref()],
+        bump = partner_config.bump
+    )]
+    pub partner_config: Account<'info, Config>,
+    #[account(
+        seeds = [b"config", platform_config.authority.as_ref()],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"price_feed", partner_config.key().as_ref()],
+        bump = partner_price_feed.bump
+    )]
+    pub partner_price_feed: Account<'info, PriceFeed>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config", authority.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RegistryEntry::INIT_SPACE,
+        seeds = [b"registry", registry.config_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub registry_entry: Account<'info, RegistryEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePriceFeed<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PriceFeed::INIT_SPACE,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PublishPrice<'info> {
+    #[account(
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+}
+
+#[derive(Accounts)]
+pub struct SetSpender<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePricing<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+}
+
+#[derive(Accounts)]
+pub struct PostCorrection<'info> {
+    pub authority: Signer<'info>,
+    pub guardian: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        has_one = guardian,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: user is verified via the UserCredit account.
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"credit", user.key().as_ref()],
+        bump = user_credit.bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+}
+
+#[derive(Accounts)]
+#[instruction(key: Pubkey)]
+pub struct RegisterViewer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Viewer::INIT_SPACE,
+        seeds = [b"viewer", config.key().as_ref(), key.as_ref()],
+        bump
+    )]
+    pub viewer: Account<'info, Viewer>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveViewer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        seeds = [b"viewer", config.key().as_ref(), viewer.key.as_ref()],
+        bump = viewer.bump
+    )]
+    pub viewer: Account<'info, Viewer>,
+}
+
+#[derive(Accounts)]
+#[instruction(endpoint_hash: [u8; 32])]
+pub struct RegisterWebhook<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Webhook::INIT_SPACE,
+        seeds = [b"webhook", config.key().as_ref(), endpoint_hash.as_ref()],
+        bump
+    )]
+    pub webhook: Account<'info, Webhook>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeWebhook<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"webhook", config.key().as_ref(), webhook.endpoint_hash.as_ref()],
+        bump = webhook.bump
+    )]
+    pub webhook: Account<'info, Webhook>,
+}
+
+#[derive(Accounts)]
+pub struct CheckDepeg<'info> {
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    pub price_update: Account<'info, PriceUpdateV2>,
+}
+
+#[derive(Accounts)]
+pub struct SuspendStaleSpender<'info> {
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AddAcceptedMint<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AcceptedMint::INIT_SPACE,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    /// Config-owned ATA that receives payments in this mint; kept if the mint is re-added.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = config,
+        associated_token::token_program = token_program
+    )]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(region: [u8; 4])]
+pub struct AddRegionVault<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RegionVault::INIT_SPACE,
+        seeds = [b"region_vault", config.key().as_ref(), region.as_ref()],
+        bump
+    )]
+    pub region_vault: Account<'info, RegionVault>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveRegionVault<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        has_one = vault_token,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Account<'info, RegionVault>,
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAcceptedMint<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        has_one = vault,
+        seeds = [b"accepted_mint", config.key().as_ref(), accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    /// Config vault for the mint; it must be drained before the mint is delisted, or
+    /// attest_reserves would stop counting it.
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct PayStable<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", user.key().as_ref()],
+        bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(mut)]
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Required when the depeg guard is enabled.
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Platform config's ATA for the paying mint, required when a partner fee is configured.
+    #[account(mut)]
+    pub platform_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Volume-tier bonus table; purchases without it receive the base rate.
+    #[account(seeds = [b"price_tiers", config.key().as_ref()], bump = price_tiers.bump)]
+    pub price_tiers: Option<Account<'info, PriceTiers>>,
+    /// Promo voucher, redeemed with the matching voucher_code argument.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"voucher", config.key().as_ref(), voucher.code_hash.as_ref()],
+        bump = voucher.bump
+    )]
+    pub voucher: Option<Account<'info, Voucher>>,
+    /// Referrer's credit account, rewarded with the config's referral share.
+    #[account(
+        mut,
+        seeds = [b"credit", referrer_credit.user.as_ref()],
+        bump = referrer_credit.bump
+    )]
+    pub referrer_credit: Option<Account<'info, UserCredit>>,
+    /// Regional vault registration, required once the config has registered regions.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+    /// Bonus campaign, applied while the current time is inside its window.
+    #[account(
+        has_one = config,
+        seeds = [b"campaign", config.key().as_ref(), campaign.campaign_id.to_le_bytes().as_ref()],
+        bump = campaign.bump
+    )]
+    pub campaign: Option<Account<'info, Campaign>>,
+    /// Membership NFT token account held by the user, paired with membership_metadata.
+    pub membership_token: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Metaplex metadata of membership_token's mint, verified in require_membership.
+    pub membership_metadata: Option<UncheckedAccount<'info>>,
+    /// KANG holder bonus table, paired with holder_token.
+    #[account(seeds = [b"holder_tiers", config.key().as_ref()], bump = holder_tiers.bump)]
+    pub holder_tiers: Option<Account<'info, HolderTiers>>,
+    /// User's KANG token account; its balance selects the holder tier.
+    pub holder_token: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Price feed, required when a curve-mode sale moves the spot price.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteCredits<'info> {
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    #[account(address = accepted_mint.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: the wallet being quoted; only its key is read.
+    pub user: UncheckedAccount<'info>,
+    /// User's credit account, whose carried remainder counts toward the purchase.
+    #[account(seeds = [b"credit", user.key().as_ref()], bump = user_credit.bump)]
+    pub user_credit: Option<Account<'info, UserCredit>>,
+    /// Volume-tier bonus table; quotes without it use the base rate.
+    #[account(seeds = [b"price_tiers", config.key().as_ref()], bump = price_tiers.bump)]
+    pub price_tiers: Option<Account<'info, PriceTiers>>,
+    /// Promo voucher, priced with the matching voucher_code argument.
+    #[account(
+        has_one = config,
+        seeds = [b"voucher", config.key().as_ref(), voucher.code_hash.as_ref()],
+        bump = voucher.bump
+    )]
+    pub voucher: Option<Account<'info, Voucher>>,
+    /// Bonus campaign, applied while the current time is inside its window.
+    #[account(
+        has_one = config,
+        seeds = [b"campaign", config.key().as_ref(), campaign.campaign_id.to_le_bytes().as_ref()],
+        bump = campaign.bump
+    )]
+    pub campaign: Option<Account<'info, Campaign>>,
+    /// Membership NFT token account held by the user, paired with membership_metadata.
+    pub membership_token: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Metaplex metadata of membership_token's mint, verified in require_membership.
+    pub membership_metadata: Option<UncheckedAccount<'info>>,
+    /// KANG holder bonus table, paired with holder_token.
+    #[account(seeds = [b"holder_tiers", config.key().as_ref()], bump = holder_tiers.bump)]
+    pub holder_tiers: Option<Account<'info, HolderTiers>>,
+    /// User's KANG token account; its balance selects the holder tier.
+    pub holder_token: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct PayFor<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: any wallet may be funded; only its credit PDA is written.
+    pub beneficiary: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", beneficiary.key().as_ref()],
+        bump
+    )]
+    pub beneficiary_credit: Account<'info, UserCredit>,
+    #[account(mut)]
+    pub payer_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Required when the depeg guard is enabled.
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Platform config's ATA for the paying mint, required when a partner fee is configured.
+    #[account(mut)]
+    pub platform_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Volume-tier bonus table; purchases without it receive the base rate.
+    #[account(seeds = [b"price_tiers", config.key().as_ref()], bump = price_tiers.bump)]
+    pub price_tiers: Option<Account<'info, PriceTiers>>,
+    /// Regional vault registration, required once the config has registered regions.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+    /// Price feed, required when a curve-mode sale moves the spot price.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+}
+
+#[derive(Accounts)]
+pub struct PayAnyToken<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", user.key().as_ref()],
+        bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    /// Vault receiving the swap output; must hold an accepted stablecoin.
+    #[account(mut)]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    /// CHECK: address-constrained to the Jupiter aggregator program.
+    #[account(address = JUPITER_PROGRAM_ID)]
+    pub jupiter_program: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Required when the depeg guard is enabled.
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Platform config's ATA for the paying mint, required when a partner fee is configured.
+    #[account(mut)]
+    pub platform_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Regional vault registration, required once the config has registered regions.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+    /// Price feed, required when a curve-mode sale moves the spot price.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSubscription<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", config.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        close = user,
+        has_one = user,
+        seeds = [b"subscription", subscription.config.as_ref(), user.key().as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+}
+
+#[derive(Accounts)]
+pub struct RenewSubscription<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = user_token,
+        has_one = mint,
+        seeds = [b"subscription", config.key().as_ref(), subscription.user.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", subscription.user.as_ref()],
+        bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(mut)]
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Required when the depeg guard is enabled.
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Platform config's ATA for the paying mint, required when a partner fee is configured.
+    #[account(mut)]
+    pub platform_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Regional vault registration, required once the config has registered regions.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+    /// Price feed, required when a curve-mode sale moves the spot price.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutoTopUp<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + AutoTopUp::INIT_SPACE,
+        seeds = [b"auto_top_up", config.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub auto_top_up: Account<'info, AutoTopUp>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DisableAutoTopUp<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        close = user,
+        has_one = user,
+        seeds = [b"auto_top_up", auto_top_up.config.as_ref(), user.key().as_ref()],
+        bump = auto_top_up.bump
+    )]
+    pub auto_top_up: Account<'info, AutoTopUp>,
+}
+
+#[derive(Accounts)]
+pub struct AutoTopUpRefill<'info> {
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        has_one = config,
+        has_one = user_token,
+        has_one = mint,
+        seeds = [b"auto_top_up", config.key().as_ref(), auto_top_up.user.as_ref()],
+        bump = auto_top_up.bump
+    )]
+    pub auto_top_up: Account<'info, AutoTopUp>,
+    #[account(
+        mut,
+        seeds = [b"credit", auto_top_up.user.as_ref()],
+        bump = user_credit.bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(mut)]
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Required when the depeg guard is enabled.
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Platform config's ATA for the paying mint, required when a partner fee is configured.
+    #[account(mut)]
+    pub platform_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Regional vault registration, required once the config has registered regions.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+    /// Price feed, required when a curve-mode sale moves the spot price.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id: u32)]
+pub struct CreateCampaign<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Campaign::INIT_SPACE,
+        seeds = [b"campaign", config.key().as_ref(), campaign_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id: u32)]
+pub struct UpdateCampaign<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"campaign", config.key().as_ref(), campaign_id.to_le_bytes().as_ref()],
+        bump = campaign.bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id: u32)]
+pub struct CloseCampaign<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        seeds = [b"campaign", config.key().as_ref(), campaign_id.to_le_bytes().as_ref()],
+        bump = campaign.bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+#[instruction(code_hash: [u8; 32])]
+pub struct CreateVoucher<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Voucher::INIT_SPACE,
+        seeds = [b"voucher", config.key().as_ref(), code_hash.as_ref()],
+        bump
+    )]
+    pub voucher: Account<'info, Voucher>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVoucher<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        seeds = [b"voucher", config.key().as_ref(), voucher.code_hash.as_ref()],
+        bump = voucher.bump
+    )]
+    pub voucher: Account<'info, Voucher>,
+}
+
+#[derive(Accounts)]
+#[instruction(serial_hash: [u8; 32])]
+pub struct CreateCard<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PrepaidCard::INIT_SPACE,
+        seeds = [b"card", config.key().as_ref(), serial_hash.as_ref()],
+        bump
+    )]
+    pub card: Account<'info, PrepaidCard>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemCard<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: receives the card's rent; must be the config authority that funded it.
+    #[account(mut, address = config.authority)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        seeds = [b"card", config.key().as_ref(), card.serial_hash.as_ref()],
+        bump = card.bump
+    )]
+    pub card: Account<'info, PrepaidCard>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", user.key().as_ref()],
+        bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"card_commit", config.key().as_ref(), user.key().as_ref()],
+        bump = card_commit.bump
+    )]
+    pub card_commit: Account<'info, CardCommit>,
+}
+
+#[derive(Accounts)]
+pub struct CommitCard<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + CardCommit::INIT_SPACE,
+        seeds = [b"card_commit", config.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub card_commit: Account<'info, CardCommit>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(package_id: u32)]
+pub struct CreatePackage<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + CreditPackage::INIT_SPACE,
+        seeds = [b"package", config.key().as_ref(), package_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub package: Account<'info, CreditPackage>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(package_id: u32)]
+pub struct ClosePackage<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        seeds = [b"package", config.key().as_ref(), package_id.to_le_bytes().as_ref()],
+        bump = package.bump
+    )]
+    pub package: Account<'info, CreditPackage>,
+}
+
+#[derive(Accounts)]
+#[instruction(package_id: u32)]
+pub struct BuyPackage<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", user.key().as_ref()],
+        bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(mut)]
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    #[account(
+        has_one = config,
+        seeds = [b"package", config.key().as_ref(), package_id.to_le_bytes().as_ref()],
+        bump = package.bump
+    )]
+    pub package: Account<'info, CreditPackage>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Required when the depeg guard is enabled.
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Platform config's ATA for the paying mint, required when a partner fee is configured.
+    #[account(mut)]
+    pub platform_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Regional vault registration, required once the config has registered regions.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+    /// Price feed, required when a curve-mode sale moves the spot price.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+}
+
+#[derive(Accounts)]
+#[instruction(invoice_id: u64)]
+pub struct CreateInvoice<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Invoice::INIT_SPACE,
+        seeds = [b"invoice", config.key().as_ref(), invoice_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(invoice_id: u64)]
+pub struct PayInvoice<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = payer @ CreditsError::Unauthorized,
+        seeds = [b"invoice", config.key().as_ref(), invoice_id.to_le_bytes().as_ref()],
+        bump = invoice.bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", payer.key().as_ref()],
+        bump
+    )]
+    pub payer_credit: Account<'info, UserCredit>,
+    #[account(mut)]
+    pub payer_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Required when the depeg guard is enabled.
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Platform config's ATA for the paying mint, required when a partner fee is configured.
+    #[account(mut)]
+    pub platform_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Regional vault registration, required once the config has registered regions.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+    /// Price feed, required when a curve-mode sale moves the spot price.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+}
+
+#[derive(Accounts)]
+#[instruction(invoice_id: u64)]
+pub struct CloseInvoice<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        seeds = [b"invoice", config.key().as_ref(), invoice_id.to_le_bytes().as_ref()],
+        bump = invoice.bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount_base_units: u64, credits: u64, expires_at: i64, nonce: u64)]
+pub struct PayWithQuote<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", user.key().as_ref()],
+        bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    /// Created once per quote nonce so a signed quote cannot be replayed.
+    #[account(
+        init,
+        payer = user,
+        space = 8 + QuoteReceipt::INIT_SPACE,
+        seeds = [b"quote", config.key().as_ref(), nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub quote_receipt: Account<'info, QuoteReceipt>,
+    #[account(mut)]
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    /// CHECK: instructions sysvar, read for the ed25519 verify of the quote.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Required when the depeg guard is enabled.
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    /// Platform config's ATA for the paying mint, required when a partner fee is configured.
+    #[account(mut)]
+    pub platform_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Regional vault registration, required once the config has registered regions.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+    /// Price feed, required when a curve-mode sale moves the spot price.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount_base_units: u64, nonce: u64)]
+pub struct PayWithPermit<'info> {
+    /// Pays transaction fees and rent on the user's behalf.
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    /// CHECK: permit signer, authenticated through the ed25519 verify instruction.
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", user.key().as_ref()],
+        bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    /// Created once per user nonce so a permit cannot be replayed.
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + PermitReceipt::INIT_SPACE,
+        seeds = [
+            b"permit",
+            config.key().as_ref(),
+            user.key().as_ref(),
+            nonce.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub permit_receipt: Account<'info, PermitReceipt>,
+    #[account(mut)]
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = config,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    /// CHECK: instructions sysvar, read for the ed25519 verify of the permit.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Required when the depeg guard is enabled.
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    /// Platform config's ATA for the paying mint, required when a partner fee is configured.
+    #[account(mut)]
+    pub platform_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Regional vault registration, required once the config has registered regions.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+    /// Price feed, required when a curve-mode sale moves the spot price.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+}
+
+#[derive(Accounts)]
+pub struct SetPriceTiers<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PriceTiers::INIT_SPACE,
+        seeds = [b"price_tiers", config.key().as_ref()],
+        bump
+    )]
+    pub price_tiers: Account<'info, PriceTiers>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetHolderTiers<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + HolderTiers::INIT_SPACE,
+        seeds = [b"holder_tiers", config.key().as_ref()],
+        bump
+    )]
+    pub holder_tiers: Account<'info, HolderTiers>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUsdc<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub vault_usdc: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub treasury_usdc: InterfaceAccount<'info, TokenAccount>,
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Regional registration of the vault being drained, checked when given.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStable<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        has_one = config,
+        has_one = mint,
+        seeds = [b"accepted_mint", config.key().as_ref(), mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub treasury_token: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Regional registration of the vault being drained, checked when given.
+    #[account(
+        has_one = config,
+        seeds = [b"region_vault", config.key().as_ref(), region_vault.region.as_ref()],
+        bump = region_vault.bump
+    )]
+    pub region_vault: Option<Account<'info, RegionVault>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLookupTable<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: PDA that owns the config's lookup tables; never holds data.
+    #[account(seeds = [b"alt_authority", config.key().as_ref()], bump)]
+    pub lookup_table_authority: UncheckedAccount<'info>,
+    /// CHECK: derived from the authority PDA and recent slot, checked in the handler.
+    #[account(mut)]
+    pub lookup_table: UncheckedAccount<'info>,
+    /// CHECK: address lookup table program.
+    #[account(address = alt_program::ID)]
+    pub address_lookup_table_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageLookupTable<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: PDA that owns the config's lookup tables; never holds data.
+    #[account(seeds = [b"alt_authority", config.key().as_ref()], bump)]
+    pub lookup_table_authority: UncheckedAccount<'info>,
+    /// CHECK: must be the lookup table recorded on the config.
+    #[account(mut, address = config.lookup_table @ CreditsError::InvalidLookupTable)]
+    pub lookup_table: UncheckedAccount<'info>,
+    /// CHECK: address lookup table program.
+    #[account(address = alt_program::ID)]
+    pub address_lookup_table_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseLookupTable<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: PDA that owns the config's lookup tables; never holds data.
+    #[account(seeds = [b"alt_authority", config.key().as_ref()], bump)]
+    pub lookup_table_authority: UncheckedAccount<'info>,
+    /// CHECK: ownership is enforced by the lookup table program.
+    #[account(mut)]
+    pub lookup_table: UncheckedAccount<'info>,
+    /// CHECK: address lookup table program.
+    #[account(address = alt_program::ID)]
+    pub address_lookup_table_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeJournal<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Journal::INIT_SPACE,
+        seeds = [b"journal", config.key().as_ref()],
+        bump
+    )]
+    pub journal: Account<'info, Journal>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSolVault<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SolVault::INIT_SPACE,
+        seeds = [b"sol_vault", config.key().as_ref()],
+        bump
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PaySol<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", user.key().as_ref()],
+        bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"sol_vault", config.key().as_ref()],
+        bump = sol_vault.bump
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+    pub price_update: Account<'info, PriceUpdateV2>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// Price feed, required when a curve-mode sale moves the spot price.
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"price_feed", config.key().as_ref()],
+        bump = price_feed.bump
+    )]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSol<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"sol_vault", config.key().as_ref()],
+        bump = sol_vault.bump
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+    /// CHECK: any system account chosen by the authority to receive SOL.
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+}
+
+#[derive(Accounts)]
+pub struct AttestReserves<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(address = config.usdc_mint)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    /// The config's canonical USDC vault, so reserves cannot be attested from a decoy account.
+    #[account(
+        associated_token::mint = usdc_mint,
+        associated_token::authority = config,
+        associated_token::token_program = token_program
+    )]
+    pub vault_usdc: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Attestation::INIT_SPACE,
+        seeds = [
+            b"attestation",
+            config.key().as_ref(),
+            config.attestation_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+    /// Required once SOL payments are enabled.
+    #[account(seeds = [b"sol_vault", config.key().as_ref()], bump = sol_vault.bump)]
+    pub sol_vault: Option<Account<'info, SolVault>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpRent<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: any account owned by this program; only lamports are added.
+    #[account(mut, owner = crate::ID)]
+    pub target: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: loaded raw because an older layout does not deserialize; checked in migrate_account.
+    #[account(mut, owner = crate::ID, seeds = [b"config", authority.key().as_ref()], bump)]
+    pub config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateUserCredit<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: only used to derive the credit PDA.
+    pub user: UncheckedAccount<'info>,
+    /// CHECK: loaded raw because an older layout does not deserialize; checked in migrate_account.
+    #[account(mut, owner = crate::ID, seeds = [b"credit", user.key().as_ref()], bump)]
+    pub user_credit: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(feature = "devnet-faucet")]
+#[derive(Accounts)]
+pub struct Faucet<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", user.key().as_ref()],
+        bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + FaucetClaim::INIT_SPACE,
+        seeds = [b"faucet", config.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub faucet_claim: Account<'info, FaucetClaim>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    pub system_program: Program<'info, System>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+}
+
+#[derive(Accounts)]
+pub struct GiftCredits<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"credit", sender.key().as_ref()],
+        bump = sender_credit.bump
+    )]
+    pub sender_credit: Account<'info, UserCredit>,
+    /// CHECK: any wallet may receive a gift; only its credit PDA is written.
+    pub recipient: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_credit: Account<'info, UserCredit>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(id_hash: [u8; 32])]
+pub struct LinkExternalId<'info> {
+    #[account(mut)]
+    pub spender: Signer<'info>,
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = spender,
+        space = 8 + ExternalIdLink::INIT_SPACE,
+        seeds = [b"external_id", config.key().as_ref(), id_hash.as_ref()],
+        bump
+    )]
+    pub external_id: Account<'info, ExternalIdLink>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlinkExternalId<'info> {
+    #[account(mut)]
+    pub spender: Signer<'info>,
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = spender,
+        has_one = config,
+        seeds = [b"external_id", config.key().as_ref(), external_id.id_hash.as_ref()],
+        bump = external_id.bump
+    )]
+    pub external_id: Account<'info, ExternalIdLink>,
+}
+
+#[derive(Accounts)]
+#[instruction(airdrop_id: u32)]
+pub struct CreateAirdrop<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", authority.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Airdrop::INIT_SPACE,
+        seeds = [b"airdrop", config.key().as_ref(), airdrop_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub airdrop: Account<'info, Airdrop>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(airdrop_id: u32, index: u32)]
+pub struct ClaimAirdrop<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"airdrop", config.key().as_ref(), airdrop_id.to_le_bytes().as_ref()],
+        bump = airdrop.bump
+    )]
+    pub airdrop: Account<'info, Airdrop>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + AirdropReceipt::INIT_SPACE,
+        seeds = [b"airdrop_claim", airdrop.key().as_ref(), index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, AirdropReceipt>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", user.key().as_ref()],
+        bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    pub system_program: Program<'info, System>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeCredit<'info> {
+    #[account(mut)]
+    pub spender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: user is verified via the UserCredit account.
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"credit", user.key().as_ref()],
+        bump = user_credit.bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Second spender signature, required for debits at or above the co-sign threshold.
+    pub co_spender: Option<Signer<'info>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+}
+
+#[derive(Accounts)]
+#[instruction(org_id: u32)]
+pub struct CreateOrg<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Org::INIT_SPACE,
+        seeds = [
+            b"org",
+            config.key().as_ref(),
+            owner.key().as_ref(),
+            org_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub org: Account<'info, Org>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + UserCredit::INIT_SPACE,
+        seeds = [b"credit", org.key().as_ref()],
+        bump
+    )]
+    pub org_credit: Account<'info, UserCredit>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOrg<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [
+            b"org",
+            org.config.as_ref(),
+            owner.key().as_ref(),
+            org.org_id.to_le_bytes().as_ref()
+        ],
+        bump = org.bump
+    )]
+    pub org: Account<'info, Org>,
+}
+
+#[derive(Accounts)]
+pub struct TouchOrg<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        has_one = owner,
+        seeds = [
+            b"org",
+            org.config.as_ref(),
+            owner.key().as_ref(),
+            org.org_id.to_le_bytes().as_ref()
+        ],
+        bump = org.bump
+    )]
+    pub org: Account<'info, Org>,
+    #[account(
+        mut,
+        seeds = [b"credit", org.key().as_ref()],
+        bump = org_credit.bump
+    )]
+    pub org_credit: Account<'info, UserCredit>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeOrgCredit<'info> {
+    #[account(mut)]
+    pub spender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        has_one = config,
+        seeds = [
+            b"org",
+            config.key().as_ref(),
+            org.owner.as_ref(),
+            org.org_id.to_le_bytes().as_ref()
+        ],
+        bump = org.bump
+    )]
+    pub org: Account<'info, Org>,
+    /// CHECK: member is verified against the org's member list.
+    pub member: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"credit", org.key().as_ref()],
+        bump = org_credit.bump
+    )]
+    pub org_credit: Account<'info, UserCredit>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Second spender signature, required for debits at or above the co-sign threshold.
+    pub co_spender: Option<Signer<'info>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+}
+
+#[derive(Accounts)]
+#[instruction(manifest_id: u32)]
+pub struct CommitManifest<'info> {
+    #[account(mut)]
+    pub spender: Signer<'info>,
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = spender,
+        space = 8 + Manifest::INIT_SPACE,
+        seeds = [b"manifest", config.key().as_ref(), manifest_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub manifest: Account<'info, Manifest>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(manifest_id: u32)]
+pub struct ConsumeWithManifest<'info> {
+    #[account(mut)]
+    pub spender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: user is verified via the UserCredit account.
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"credit", user.key().as_ref()],
+        bump = user_credit.bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"manifest", config.key().as_ref(), manifest_id.to_le_bytes().as_ref()],
+        bump = manifest.bump
+    )]
+    pub manifest: Account<'info, Manifest>,
+    #[account(
+        init_if_needed,
+        payer = spender,
+        space = 8 + ManifestUsage::INIT_SPACE,
+        seeds = [b"manifest_usage", manifest.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub usage: Account<'info, ManifestUsage>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    pub system_program: Program<'info, System>,
+    /// Second spender signature, required for debits at or above the co-sign threshold.
+    pub co_spender: Option<Signer<'info>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+}
+
+#[derive(Accounts)]
+#[instruction(spend_id: [u8; 32])]
+pub struct RequestSpend<'info> {
+    #[account(mut)]
+    pub spender: Signer<'info>,
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: user is verified via the UserCredit account.
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"credit", user.key().as_ref()],
+        bump = user_credit.bump,
+        constraint = user_credit.user == user.key() @ CreditsError::InvalidOwner
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(
+        init,
+        payer = spender,
+        space = 8 + PendingSpend::INIT_SPACE,
+        seeds = [
+            b"pending_spend",
+            config.key().as_ref(),
+            user.key().as_ref(),
+            spend_id.as_ref()
+        ],
+        bump
+    )]
+    pub pending_spend: Account<'info, PendingSpend>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(spend_id: [u8; 32])]
+pub struct AcknowledgeSpend<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"credit", user.key().as_ref()],
+        bump = user_credit.bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(
+        mut,
+        has_one = user,
+        seeds = [
+            b"pending_spend",
+            pending_spend.config.as_ref(),
+            user.key().as_ref(),
+            spend_id.as_ref()
+        ],
+        bump = pending_spend.bump
+    )]
+    pub pending_spend: Account<'info, PendingSpend>,
+    #[account(address = pending_spend.config)]
+    pub config: Account<'info, Config>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(spend_id: [u8; 32])]
+pub struct RejectSpend<'info> {
+    pub user: Signer<'info>,
+    /// CHECK: rent refund destination, verified against the pending spend.
+    #[account(mut)]
+    pub spender: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = spender,
+        has_one = user,
+        has_one = spender,
+        seeds = [
+            b"pending_spend",
+            pending_spend.config.as_ref(),
+            user.key().as_ref(),
+            spend_id.as_ref()
+        ],
+        bump = pending_spend.bump
+    )]
+    pub pending_spend: Account<'info, PendingSpend>,
+}
+
+#[derive(Accounts)]
+#[instruction(spend_id: [u8; 32])]
+pub struct ConfirmSpend<'info> {
+    #[account(mut)]
+    pub spender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: user is verified via the UserCredit account.
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"credit", user.key().as_ref()],
+        bump = user_credit.bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(
+        mut,
+        close = spender,
+        has_one = user,
+        has_one = spender,
+        seeds = [
+            b"pending_spend",
+            config.key().as_ref(),
+            user.key().as_ref(),
+            spend_id.as_ref()
+        ],
+        bump = pending_spend.bump
+    )]
+    pub pending_spend: Account<'info, PendingSpend>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Second spender signature, required for debits at or above the co-sign threshold.
+    pub co_spender: Option<Signer<'info>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+}
+
+#[derive(Accounts)]
+#[instruction(job_id: [u8; 32])]
+pub struct AuthorizeJob<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(seeds = [b"config", config.authority.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Authorization::INIT_SPACE,
+        seeds = [
+            b"authorization",
+            config.key().as_ref(),
+            user.key().as_ref(),
+            job_id.as_ref()
+        ],
+        bump
+    )]
+    pub authorization: Account<'info, Authorization>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: instructions sysvar, required when the CPI guard is enabled.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(job_id: [u8; 32])]
+pub struct RevokeJobAuthorization<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        close = user,
+        has_one = user,
+        seeds = [
+            b"authorization",
+            authorization.config.as_ref(),
+            user.key().as_ref(),
+            job_id.as_ref()
+        ],
+        bump = authorization.bump
+    )]
+    pub authorization: Account<'info, Authorization>,
+}
+
+#[derive(Accounts)]
+#[instruction(job_id: [u8; 32])]
+pub struct ConsumeJobCredit<'info> {
+    #[account(mut)]
+    pub spender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.authority.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: user is verified via the UserCredit account.
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"credit", user.key().as_ref()],
+        bump = user_credit.bump
+    )]
+    pub user_credit: Account<'info, UserCredit>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = spender,
+        seeds = [
+            b"authorization",
+            config.key().as_ref(),
+            user.key().as_ref(),
+            job_id.as_ref()
+        ],
+        bump = authorization.bump
+    )]
+    pub authorization: Account<'info, Authorization>,
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Account<'info, GlobalState>,
+    /// Second spender signature, required for debits at or above the co-sign threshold.
+    pub co_spender: Option<Signer<'info>>,
+    /// Journal ring buffer, required once the config has enabled journaling.
+    #[account(mut, seeds = [b"journal", config.key().as_ref()], bump = journal.bump)]
+    pub journal: Option<Account<'info, Journal>>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalState {
+    pub super_authority: Pubkey,
+    pub frozen: bool,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub authority: Pubkey,
+    pub spender: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub credit_unit: u64,
+    pub bump: u8,
+    pub spender_last_active_slot: u64,
+    pub spender_inactivity_slots: u64,
+    pub usdc_feed_id: [u8; 32],
+    pub depeg_band_bps: u16,
+    pub depeg_max_age_secs: u64,
+    pub depeg_tripped: bool,
+    pub co_spender: Pubkey,
+    pub co_sign_threshold: u64,
+    pub cpi_guard: bool,
+    pub cpi_allowed_callers: [Pubkey; MAX_CPI_CALLERS],
+    pub guardian: Pubkey,
+    pub total_credits_issued: u64,
+    pub total_credits_consumed: u64,
+    pub attestation_count: u64,
+    pub platform_fee_bps: u16,
+    pub platform_config: Pubkey,
+    pub sol_feed_id: [u8; 32],
+    pub sol_max_age_secs: u64,
+    pub sol_max_conf_bps: u16,
+    pub journal_enabled: bool,
+    pub dormancy_secs: i64,
+    pub pending_confirm_delay_secs: i64,
+    pub lookup_table: Pubkey,
+    pub referral_bps: u16,
+    pub sunset_at: i64,
+    pub region_count: u8,
+    pub min_purchase_base_units: u64,
+    pub max_purchase_base_units: u64,
+    pub membership_collection: Pubkey,
+    pub membership_discount_bps: u16,
+    pub program_version: u32,
+    pub quote_signer: Pubkey,
+    pub pricing_mode: PricingMode,
+    pub curve_base_unit: u64,
+    pub curve_step_credits: u64,
+    pub curve_step_bps: u16,
+    pub curve_max_unit: u64,
+    pub variant_count: u8,
+    pub pricing_variants: [PricingVariant; MAX_PRICING_VARIANTS],
+    pub accepted_mint_count: u16,
+    pub job_authorization_required: bool,
+    pub credits_sold: u64,
+    pub max_bonus_bps: u16,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PricingMode {
+    Flat,
+    Curve,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceCategory {
+    Purchase,
+    Consumption,
+    CorrectionCredit,
+    CorrectionDebit,
+    Faucet,
+    Airdrop,
+    Referral,
+    GiftSent,
+    GiftReceived,
+}
+
+impl BalanceCategory {
+    pub fn is_debit(self) -> bool {
+        matches!(
+            self,
+            BalanceCategory::Consumption
+                | BalanceCategory::CorrectionDebit
+                | BalanceCategory::GiftSent
+        )
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Journal {
+    pub config: Pubkey,
+    pub next_seq: u64,
+    pub entries: [JournalEntry; JOURNAL_CAPACITY],
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct JournalEntry {
+    pub seq: u64,
+    pub kind: JournalKind,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum JournalKind {
+    Paid,
+    PaidSol,
+    Consumed,
+    CorrectionCredit,
+    CorrectionDebit,
+    Faucet,
+    WithdrawnStable,
+    WithdrawnSol,
+    Airdrop,
+    Referral,
+    PrepaidCard,
+    GiftSent,
+    GiftReceived,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AcceptedMint {
+    pub config: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub bump: u8,
+    pub vault: Pubkey,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Org {
+    pub config: Pubkey,
+    pub owner: Pubkey,
+    pub org_id: u32,
+    pub member_count: u8,
+    pub members: [Pubkey; MAX_ORG_MEMBERS],
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ExternalIdLink {
+    pub config: Pubkey,
+    pub id_hash: [u8; 32],
+    pub user: Pubkey,
+    pub user_credit: Pubkey,
+    pub updated_at: i64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Airdrop {
+    pub config: Pubkey,
+    pub airdrop_id: u32,
+    pub root: [u8; 32],
+    pub total_budget: u64,
+    pub claimed: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AirdropReceipt {
+    pub airdrop: Pubkey,
+    pub index: u32,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Subscription {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub user_token: Pubkey,
+    pub mint: Pubkey,
+    pub plan_id: u32,
+    pub period_secs: i64,
+    pub amount_base_units: u64,
+    pub next_renewal_at: i64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AutoTopUp {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub user_token: Pubkey,
+    pub mint: Pubkey,
+    pub threshold_credits: u64,
+    pub amount_base_units: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Campaign {
+    pub config: Pubkey,
+    pub campaign_id: u32,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub bonus_bps: u16,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+impl Campaign {
+    // Bonus credits for a purchase made now; purchases outside the window are rejected.
+    pub fn bonus_credits(&self, base_credits: u64) -> Result<u64> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= self.starts_at && now < self.ends_at,
+            CreditsError::CampaignInactive
+        );
+        let bonus = u128::from(base_credits)
+            .checked_mul(u128::from(self.bonus_bps))
+            .ok_or(CreditsError::Overflow)?
+            / u128::from(BPS_DENOMINATOR);
+        u64::try_from(bonus).map_err(|_| error!(CreditsError::Overflow))
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Voucher {
+    pub config: Pubkey,
+    pub code_hash: [u8; 32],
+    pub discount_bps: u16,
+    pub bonus_credits: u64,
+    pub max_uses: u32,
+    pub uses: u32,
+    pub expires_at: i64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+impl Voucher {
+    // Extra credits granted on top of `base_credits`; the caller records the use.
+    pub fn bonus_credits(&self, code: &str, base_credits: u64) -> Result<u64> {
+        require!(
+            code.len() <= MAX_VOUCHER_CODE_LEN,
+            CreditsError::InvalidVoucherCode
+        );
+        require!(
+            hashv(&[code.as_bytes()]).to_bytes() == self.code_hash,
+            CreditsError::InvalidVoucherCode
+        );
+        require!(self.uses < self.max_uses, CreditsError::VoucherExhausted);
+        require!(
+            self.expires_at == 0 || Clock::get()?.unix_timestamp < self.expires_at,
+            CreditsError::VoucherExpired
+        );
+        discount_credits(base_credits, self.discount_bps)?
+            .checked_add(self.bonus_credits)
+            .ok_or_else(|| error!(CreditsError::Overflow))
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Invoice {
+    pub config: Pubkey,
+    pub invoice_id: u64,
+    pub payer: Pubkey,
+    pub mint: Pubkey,
+    pub amount_base_units: u64,
+    pub credits: u64,
+    pub expires_at: i64,
+    pub reference_hash: [u8; 32],
+    pub paid_at: i64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct QuoteReceipt {
+    pub config: Pubkey,
+    pub nonce: u64,
+    pub user: Pubkey,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PermitReceipt {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub relayer: Pubkey,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PrepaidCard {
+    pub config: Pubkey,
+    pub serial_hash: [u8; 32],
+    pub credits: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CardCommit {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub commitment: [u8; 32],
+    pub slot: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CreditPackage {
+    pub config: Pubkey,
+    pub package_id: u32,
+    pub price_usd_base_units: u64,
+    pub credits: u64,
+    pub bonus_credits: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PriceTiers {
+    pub config: Pubkey,
+    pub tier_count: u8,
+    pub tiers: [PriceTier; MAX_PRICE_TIERS],
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+impl PriceTiers {
+    // Bonus credits from the highest tier whose threshold the purchase reaches.
+    pub fn bonus_credits(&self, usd_base_units: u64, base_credits: u64) -> Result<u64> {
+        let bonus_bps = self.tiers[..usize::from(self.tier_count)]
+            .iter()
+            .rev()
+            .find(|tier| usd_base_units >= tier.min_usd_base_units)
+            .map_or(0, |tier| tier.bonus_bps);
+        let bonus = u128::from(base_credits)
+            .checked_mul(u128::from(bonus_bps))
+            .ok_or(CreditsError::Overflow)?
+            / u128::from(BPS_DENOMINATOR);
+        u64::try_from(bonus).map_err(|_| error!(CreditsError::Overflow))
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct HolderTiers {
+    pub config: Pubkey,
+    pub mint: Pubkey,
+    pub tier_count: u8,
+    pub tiers: [HolderTier; MAX_HOLDER_TIERS],
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+impl HolderTiers {
+    // Bonus credits from the highest tier whose balance threshold the holder reaches.
+    pub fn bonus_credits(&self, balance: u64, base_credits: u64) -> Result<u64> {
+        let bonus_bps = self.tiers[..usize::from(self.tier_count)]
+            .iter()
+            .rev()
+            .find(|tier| balance >= tier.min_balance)
+            .map_or(0, |tier| tier.bonus_bps);
+        let bonus = u128::from(base_credits)
+            .checked_mul(u128::from(bonus_bps))
+            .ok_or(CreditsError::Overflow)?
+            / u128::from(BPS_DENOMINATOR);
+        u64::try_from(bonus).map_err(|_| error!(CreditsError::Overflow))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PricingVariant {
+    pub variant_id: u16,
+    pub bonus_bps: u16,
+}
+
+impl PricingVariant {
+    // Bonus credits this arm adds on top of `base_credits`.
+    pub fn bonus_credits(&self, base_credits: u64) -> Result<u64> {
+        bps_of(base_credits, self.bonus_bps)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct HolderTier {
+    pub min_balance: u64,
+    pub bonus_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreditQuote {
+    pub charged_base_units: u64,
+    pub dust_base_units: u64,
+    pub base_credits: u64,
+    pub bonus_credits: u64,
+    pub total_credits: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PriceTier {
+    pub min_usd_base_units: u64,
+    pub bonus_bps: u16,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RegionVault {
+    pub config: Pubkey,
+    pub region: [u8; 4],
+    pub vault_token: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SolVault {
+    pub config: Pubkey,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation {
+    pub config: Pubkey,
+    pub index: u64,
+    pub vault_balance: u64,
+    pub sol_vault_lamports: u64,
+    pub liabilities_credits: u64,
+    pub liabilities_base_units: u64,
+    pub stats_hash: [u8; 32],
+    pub timestamp: i64,
+    pub slot: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Viewer {
+    pub config: Pubkey,
+    pub key: Pubkey,
+    #[max_len(MAX_LABEL_LEN)]
+    pub label: String,
+    pub registered_at: i64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Webhook {
+    pub config: Pubkey,
+    pub endpoint_hash: [u8; 32],
+    pub key_fingerprint: [u8; 32],
+    pub registered_at: i64,
+    pub revoked_at: i64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PriceFeed {
+    pub config: Pubkey,
+    pub usdc_mint: Pubkey,
+    /// Effective unit: the current curve step when the config prices on a curve.
+    pub credit_unit: u64,
+    pub membership_discount_bps: u16,
+    pub platform_fee_bps: u16,
+    pub updated_slot: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct UserCredit {
+    pub user: Pubkey,
+    pub credits: u64,
+    pub bump: u8,
+    pub last_active_at: i64,
+    pub remainder_base_units: u64,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Manifest {
+    pub config: Pubkey,
+    pub manifest_id: u32,
+    pub root: [u8; 32],
+    pub total_cap: u64,
+    pub total_debited: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ManifestUsage {
+    pub manifest: Pubkey,
+    pub user: Pubkey,
+    pub debited: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PendingSpend {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub spender: Pubkey,
+    pub spend_id: [u8; 32],
+    pub amount: u64,
+    pub requested_at: i64,
+    pub confirm_after: i64,
+    pub acknowledged: bool,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Registry {
+    pub config_count: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[cfg(feature = "devnet-faucet")]
+#[account]
+#[derive(InitSpace)]
+pub struct FaucetClaim {
+    pub user: Pubkey,
+    pub last_claim_day: i64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RegistryEntry {
+    pub index: u64,
+    pub config: Pubkey,
+    pub authority: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+// Fixed-size accounts are created via CPI, which caps allocation per instruction.
+const _: () = {
+    assert!(8 + GlobalState::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Config::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Registry::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + RegistryEntry::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + SolVault::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + AcceptedMint::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + RegionVault::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + PriceTiers::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + CreditPackage::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Voucher::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Campaign::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + HolderTiers::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Invoice::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + PrepaidCard::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + CardCommit::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + QuoteReceipt::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + PermitReceipt::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Org::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Subscription::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + AutoTopUp::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Airdrop::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + ExternalIdLink::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + AirdropReceipt::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Journal::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Attestation::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Viewer::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Webhook::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + PriceFeed::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + UserCredit::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Authorization::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + PendingSpend::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + Manifest::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    assert!(8 + ManifestUsage::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+    #[cfg(feature = "devnet-faucet")]
+    assert!(8 + FaucetClaim::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+};
+
+#[account]
+#[derive(InitSpace)]
+pub struct Authorization {
+    pub config: Pubkey,
+    pub spender: Pubkey,
+    pub user: Pubkey,
+    pub job_id: [u8; 32],
+    pub max_cost: u64,
+    pub consumed: u64,
+    pub bump: u8,
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+#[event]
+pub struct Paid {
+    pub user: Pubkey,
+    pub beneficiary: Pubkey,
+    pub references: Vec<Pubkey>,
+    pub amount_base_units: u64,
+    pub credits_added: u64,
+    pub new_balance: u64,
+    pub mint: Pubkey,
+    pub received_base_units: u64,
+    pub bonus_credits: u64,
+    pub variant_id: Option<u16>,
+}
+
+#[event]
+pub struct SubscriptionCreated {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub plan_id: u32,
+    pub period_secs: i64,
+    pub amount_base_units: u64,
+}
+
+#[event]
+pub struct SubscriptionRenewed {
+    pub user: Pubkey,
+    pub plan_id: u32,
+    pub received_base_units: u64,
+    pub credits_added: u64,
+    pub new_balance: u64,
+    pub next_renewal_at: i64,
+}
+
+#[event]
+pub struct AutoToppedUp {
+    pub user: Pubkey,
+    pub received_base_units: u64,
+    pub credits_added: u64,
+    pub new_balance: u64,
+}
+
+#[event]
+pub struct SunsetScheduled {
+    pub config: Pubkey,
+    pub sunset_at: i64,
+}
+
+#[event]
+pub struct SunsetCancelled {
+    pub config: Pubkey,
+    pub cancelled_sunset_at: i64,
+}
+
+#[event]
+pub struct Gifted {
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub memo: String,
+    pub sender_balance: u64,
+    pub recipient_balance: u64,
+}
+
+#[event]
+pub struct ExternalIdLinked {
+    pub config: Pubkey,
+    pub id_hash: [u8; 32],
+    pub user: Pubkey,
+}
+
+#[event]
+pub struct MembershipDiscountUpdated {
+    pub config: Pubkey,
+    pub collection: Pubkey,
+    pub discount_bps: u16,
+}
+
+#[event]
+pub struct ReferralPaid {
+    pub referrer: Pubkey,
+    pub user: Pubkey,
+    pub credits: u64,
+    pub new_balance: u64,
+}
+
+#[event]
+pub struct AirdropCreated {
+    pub config: Pubkey,
+    pub airdrop_id: u32,
+    pub root: [u8; 32],
+    pub total_budget: u64,
+}
+
+#[event]
+pub struct AirdropClaimed {
+    pub airdrop: Pubkey,
+    pub user: Pubkey,
+    pub index: u32,
+    pub amount: u64,
+    pub new_balance: u64,
+}
+
+#[event]
+pub struct CampaignUpdated {
+    pub config: Pubkey,
+    pub campaign_id: u32,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub bonus_bps: u16,
+}
+
+#[event]
+pub struct VoucherRedeemed {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub code_hash: [u8; 32],
+    pub bonus_credits: u64,
+    pub uses: u32,
 }
 
-#[derive(Accounts)]
-pub struct InitializeConfig<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub usdc_mint: Account<'info, Mint>,
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Config::LEN,
-        seeds = [b"config", authority.key().as_ref()],
-        bump
-    )]
-    pub config: Account<'info, Config>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct PackageCreated {
+    pub config: Pubkey,
+    pub package_id: u32,
+    pub price_usd_base_units: u64,
+    pub credits: u64,
+    pub bonus_credits: u64,
 }
 
-#[derive(Accounts)]
-pub struct SetSpender<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    #[account(
-        mut,
-        has_one = authority,
-        seeds = [b"config", authority.key().as_ref()],
-        bump = config.bump
-    )]
-    pub config: Account<'info, Config>,
+#[event]
+pub struct PackageClosed {
+    pub config: Pubkey,
+    pub package_id: u32,
 }
 
-#[derive(Accounts)]
-pub struct PayUsdc<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [b"config", config.authority.as_ref()],
-        bump = config.bump
-    )]
-    pub config: Account<'info, Config>,
-    #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + UserCredit::LEN,
-        seeds = [b"credit", user.key().as_ref()],
-        bump
-    )]
-    pub user_credit: Account<'info, UserCredit>,
-    #[account(mut)]
-    pub user_usdc: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub vault_usdc: Account<'info, TokenAccount>,
-    pub usdc_mint: Account<'info, Mint>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct PricingVariantsUpdated {
+    pub config: Pubkey,
+    pub variants: Vec<PricingVariant>,
 }
 
-#[derive(Accounts)]
-pub struct WithdrawUsdc<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    #[account(
-        mut,
-        has_one = authority,
-        seeds = [b"config", authority.key().as_ref()],
-        bump = config.bump
-    )]
-    pub config: Account<'info, Config>,
-    #[account(mut)]
-    pub vault_usdc: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub treasury_usdc: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct PricingModeUpdated {
+    pub config: Pubkey,
+    pub mode: PricingMode,
+    pub curve_base_unit: u64,
+    pub curve_step_credits: u64,
+    pub curve_step_bps: u16,
+    pub curve_max_unit: u64,
 }
 
-#[derive(Accounts)]
-pub struct ConsumeCredit<'info> {
-    #[account(mut)]
-    pub spender: Signer<'info>,
-    #[account(
-        seeds = [b"config", config.authority.as_ref()],
-        bump = config.bump
-    )]
-    pub config: Account<'info, Config>,
-    /// CHECK: user is verified via the UserCredit account.
-    pub user: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        seeds = [b"credit", user.key().as_ref()],
-        bump = user_credit.bump
-    )]
-    pub user_credit: Account<'info, UserCredit>,
+#[event]
+pub struct QuoteSignerUpdated {
+    pub config: Pubkey,
+    pub quote_signer: Pubkey,
 }
 
-#[account]
-pub struct Config {
-    pub authority: Pubkey,
-    pub spender: Pubkey,
-    pub usdc_mint: Pubkey,
-    pub credit_unit: u64,
-    pub bump: u8,
+#[event]
+pub struct QuotePaid {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub mint: Pubkey,
+    pub received_base_units: u64,
+    pub credits_added: u64,
+    pub new_balance: u64,
 }
 
-impl Config {
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 1;
+#[event]
+pub struct PermitPaid {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub relayer: Pubkey,
+    pub nonce: u64,
+    pub mint: Pubkey,
+    pub received_base_units: u64,
+    pub credits_added: u64,
+    pub new_balance: u64,
 }
 
-#[account]
-pub struct UserCredit {
+#[event]
+pub struct CardRedeemed {
+    pub config: Pubkey,
     pub user: Pubkey,
+    pub serial_hash: [u8; 32],
+    pub credits_added: u64,
+    pub new_balance: u64,
+}
+
+#[event]
+pub struct InvoiceCreated {
+    pub config: Pubkey,
+    pub invoice_id: u64,
+    pub payer: Pubkey,
+    pub mint: Pubkey,
+    pub amount_base_units: u64,
     pub credits: u64,
-    pub bump: u8,
+    pub expires_at: i64,
+    pub reference_hash: [u8; 32],
+}
+
+#[event]
+pub struct InvoicePaid {
+    pub config: Pubkey,
+    pub invoice_id: u64,
+    pub payer: Pubkey,
+    pub mint: Pubkey,
+    pub received_base_units: u64,
+    pub credits_added: u64,
+    pub new_balance: u64,
+    pub reference_hash: [u8; 32],
 }
 
-impl UserCredit {
-    pub const LEN: usize = 32 + 8 + 1;
+#[event]
+pub struct InvoiceClosed {
+    pub config: Pubkey,
+    pub invoice_id: u64,
+    pub paid: bool,
 }
 
 #[event]
-pub struct Paid {
+pub struct PackagePurchased {
     pub user: Pubkey,
+    pub package_id: u32,
+    pub mint: Pubkey,
+    pub received_base_units: u64,
+    pub credits_added: u64,
+    pub bonus_credits: u64,
+    pub new_balance: u64,
+}
+
+#[event]
+pub struct RegionVaultAdded {
+    pub config: Pubkey,
+    pub region: [u8; 4],
+    pub vault_token: Pubkey,
+}
+
+#[event]
+pub struct RegionVaultRemoved {
+    pub config: Pubkey,
+    pub region: [u8; 4],
+}
+
+#[event]
+pub struct AcceptedMintAdded {
+    pub config: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct AcceptedMintRemoved {
+    pub config: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct PlatformFeePaid {
+    pub config: Pubkey,
+    pub platform_vault: Pubkey,
     pub amount_base_units: u64,
+}
+
+#[event]
+pub struct PartnerFeeSet {
+    pub partner_config: Pubkey,
+    pub platform_config: Pubkey,
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct PaidSol {
+    pub user: Pubkey,
+    pub lamports: u64,
+    pub price: i64,
+    pub exponent: i32,
     pub credits_added: u64,
     pub new_balance: u64,
 }
 
+#[event]
+pub struct SolWithdrawn {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub lamports: u64,
+}
+
 #[event]
 pub struct Withdrawn {
     pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub mint: Pubkey,
     pub amount_base_units: u64,
 }
 
+#[cfg(feature = "devnet-faucet")]
+#[event]
+pub struct FaucetClaimed {
+    pub user: Pubkey,
+    pub credits_added: u64,
+    pub new_balance: u64,
+}
+
+#[event]
+pub struct OrgCreated {
+    pub config: Pubkey,
+    pub org: Pubkey,
+    pub owner: Pubkey,
+    pub org_id: u32,
+}
+
+#[event]
+pub struct OrgMemberUpdated {
+    pub org: Pubkey,
+    pub member: Pubkey,
+    pub added: bool,
+}
+
+#[event]
+pub struct OrgCreditUsed {
+    pub org: Pubkey,
+    pub member: Pubkey,
+    pub amount: u64,
+    pub new_balance: u64,
+}
+
 #[event]
 pub struct CreditUsed {
     pub user: Pubkey,
@@ -277,6 +7672,174 @@ pub struct CreditUsed {
     pub new_balance: u64,
 }
 
+#[event]
+pub struct ManifestCommitted {
+    pub config: Pubkey,
+    pub manifest_id: u32,
+    pub root: [u8; 32],
+    pub total_cap: u64,
+}
+
+#[event]
+pub struct SpendRequested {
+    pub user: Pubkey,
+    pub spend_id: [u8; 32],
+    pub amount: u64,
+    pub confirm_after: i64,
+}
+
+#[event]
+pub struct SpendRejected {
+    pub user: Pubkey,
+    pub spend_id: [u8; 32],
+    pub amount: u64,
+}
+
+#[event]
+pub struct JobAuthorized {
+    pub config: Pubkey,
+    pub spender: Pubkey,
+    pub user: Pubkey,
+    pub job_id: [u8; 32],
+    pub max_cost: u64,
+}
+
+#[event]
+pub struct JobCreditUsed {
+    pub user: Pubkey,
+    pub job_id: [u8; 32],
+    pub amount: u64,
+    pub consumed: u64,
+    pub max_cost: u64,
+}
+
+#[event]
+pub struct Correction {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub signed_delta: i64,
+    pub new_balance: u64,
+    pub reason: String,
+    pub evidence_hash: [u8; 32],
+    pub authority: Pubkey,
+    pub guardian: Pubkey,
+}
+
+#[event]
+pub struct ViewerRegistered {
+    pub config: Pubkey,
+    pub key: Pubkey,
+    pub label: String,
+}
+
+#[event]
+pub struct ViewerRemoved {
+    pub config: Pubkey,
+    pub key: Pubkey,
+}
+
+#[event]
+pub struct WebhookRegistered {
+    pub config: Pubkey,
+    pub endpoint_hash: [u8; 32],
+    pub key_fingerprint: [u8; 32],
+    pub registered_at: i64,
+}
+
+#[event]
+pub struct WebhookRevoked {
+    pub config: Pubkey,
+    pub endpoint_hash: [u8; 32],
+    pub revoked_at: i64,
+}
+
+#[event]
+pub struct LookupTableUpdated {
+    pub config: Pubkey,
+    pub lookup_table: Pubkey,
+    pub added: u32,
+    pub active: bool,
+}
+
+#[event]
+pub struct BalanceChanged {
+    pub user: Pubkey,
+    pub delta: i64,
+    pub new_balance: u64,
+    pub category: BalanceCategory,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub config: Pubkey,
+    pub field: ConfigField,
+    pub old_value: u64,
+    pub new_value: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigField {
+    CreditUnit,
+    ReferralBps,
+    MinPurchase,
+    MaxPurchase,
+    ProgramVersion,
+    MaxBonusBps,
+}
+
+#[event]
+pub struct PricePublished {
+    pub config: Pubkey,
+    pub credit_unit: u64,
+    pub membership_discount_bps: u16,
+    pub platform_fee_bps: u16,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DepegTripped {
+    pub config: Pubkey,
+    pub deviation_bps: u64,
+}
+
+#[event]
+pub struct GlobalFreezeSet {
+    pub super_authority: Pubkey,
+    pub frozen: bool,
+}
+
+#[event]
+pub struct ReservesAttested {
+    pub config: Pubkey,
+    pub index: u64,
+    pub vault_balance: u64,
+    pub sol_vault_lamports: u64,
+    pub liabilities_base_units: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RentToppedUp {
+    pub account: Pubkey,
+    pub payer: Pubkey,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub payer: Pubkey,
+    pub old_len: u64,
+    pub new_len: u64,
+}
+
+#[event]
+pub struct SpenderSuspended {
+    pub spender: Pubkey,
+    pub last_active_slot: u64,
+    pub slot: u64,
+}
+
 #[error_code]
 pub enum CreditsError {
     #[msg("Invalid amount")]
@@ -293,4 +7856,295 @@ pub enum CreditsError {
     Unauthorized,
     #[msg("Insufficient credits")]
     InsufficientCredits,
+    #[msg("Spender inactivity horizon not configured")]
+    InactivityDisabled,
+    #[msg("Spender is not stale")]
+    SpenderNotStale,
+    #[msg("Account is already rent exempt")]
+    AlreadyRentExempt,
+    #[msg("Account already has the current layout")]
+    AlreadyMigrated,
+    #[msg("Account is not rent exempt")]
+    NotRentExempt,
+    #[msg("Program is globally frozen")]
+    GloballyFrozen,
+    #[msg("Depeg guard not configured")]
+    DepegGuardDisabled,
+    #[msg("USDC price within band")]
+    PriceWithinBand,
+    #[msg("USDC depeg detected")]
+    DepegDetected,
+    #[msg("Missing price update account")]
+    MissingPriceUpdate,
+    #[msg("Debit exceeds job authorization")]
+    AuthorizationExceeded,
+    #[msg("Debits must go through a job authorization")]
+    JobAuthorizationRequired,
+    #[msg("Co-spender must differ from the spender")]
+    InvalidCoSpender,
+    #[msg("Debit requires a co-spender signature")]
+    CoSignatureRequired,
+    #[msg("Too many allowed CPI callers")]
+    TooManyCpiCallers,
+    #[msg("Missing instructions sysvar")]
+    MissingInstructionsSysvar,
+    #[msg("CPI caller not allowed")]
+    CpiCallerNotAllowed,
+    #[msg("Reason too long")]
+    ReasonTooLong,
+    #[msg("Label too long")]
+    LabelTooLong,
+    #[msg("Webhook already revoked")]
+    WebhookAlreadyRevoked,
+    #[msg("Invalid platform vault")]
+    InvalidPlatformVault,
+    #[msg("Faucet already claimed today")]
+    FaucetAlreadyClaimed,
+    #[msg("SOL payments are not configured")]
+    SolPaymentsDisabled,
+    #[msg("Invalid oracle price")]
+    InvalidPrice,
+    #[msg("Oracle price confidence too wide")]
+    PriceTooUncertain,
+    #[msg("Insufficient vault balance")]
+    InsufficientVaultBalance,
+    #[msg("Missing journal account")]
+    MissingJournal,
+    #[msg("Credit unit out of bounds")]
+    InvalidCreditUnit,
+    #[msg("Price feed is required when a sale moves the curve price")]
+    MissingPriceFeed,
+    #[msg("Purchase spans more pricing curve steps than one transaction may price")]
+    PurchaseSpansTooManySteps,
+    #[msg("Dormant account requires user acknowledgment")]
+    AcknowledgmentRequired,
+    #[msg("Account is not dormant")]
+    AccountNotDormant,
+    #[msg("Pending spend not yet confirmable")]
+    SpendNotConfirmable,
+    #[msg("Too many price tiers")]
+    TooManyPriceTiers,
+    #[msg("Price tiers must have ascending thresholds and bonus within 100%")]
+    InvalidPriceTier,
+    #[msg("Payment received is below the package price")]
+    InsufficientPayment,
+    #[msg("Invalid lookup table")]
+    InvalidLookupTable,
+    #[msg("Invalid voucher code")]
+    InvalidVoucherCode,
+    #[msg("Voucher has no uses left")]
+    VoucherExhausted,
+    #[msg("Voucher expired")]
+    VoucherExpired,
+    #[msg("Invalid Merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Airdrop budget exceeded")]
+    AirdropBudgetExceeded,
+    #[msg("Cannot refer yourself")]
+    SelfReferral,
+    #[msg("Memo too long")]
+    MemoTooLong,
+    #[msg("Cannot gift credits to yourself")]
+    SelfGift,
+    #[msg("Config is read-only after sunset")]
+    ConfigReadOnly,
+    #[msg("Sunset already scheduled")]
+    SunsetAlreadyScheduled,
+    #[msg("Sunset not scheduled")]
+    SunsetNotScheduled,
+    #[msg("Subscription renewal not due")]
+    RenewalNotDue,
+    #[msg("Balance is above the auto top-up threshold")]
+    AboveTopUpThreshold,
+    #[msg("Missing regional vault")]
+    MissingRegionVault,
+    #[msg("Invalid regional vault")]
+    InvalidRegionVault,
+    #[msg("Regional vault still holds funds")]
+    RegionVaultNotEmpty,
+    #[msg("Accepted mint's vault still holds funds")]
+    AcceptedMintVaultNotEmpty,
+    #[msg("Missing vault account")]
+    MissingVault,
+    #[msg("Vault is not the accepted mint's vault")]
+    InvalidVault,
+    #[msg("Missing SOL vault")]
+    MissingSolVault,
+    #[msg("Debit exceeds manifest cap")]
+    ManifestCapExceeded,
+    #[msg("Too many payment references")]
+    TooManyReferences,
+    #[msg("Payment references must be read-only")]
+    InvalidReference,
+    #[msg("Purchase below configured minimum")]
+    PurchaseBelowMinimum,
+    #[msg("Purchase above configured maximum")]
+    PurchaseAboveMaximum,
+    #[msg("Campaign window must start before it ends")]
+    InvalidCampaignWindow,
+    #[msg("Campaign is not active")]
+    CampaignInactive,
+    #[msg("Campaign has not ended")]
+    CampaignStillActive,
+    #[msg("Membership NFT is not held or not in the configured collection")]
+    InvalidMembership,
+    #[msg("Config has not been confirmed for the deployed program version")]
+    VersionMismatch,
+    #[msg("Config is already confirmed for the deployed program version")]
+    AlreadyCurrentVersion,
+    #[msg("Holder token account does not match the holder tier mint or payer")]
+    InvalidHolderToken,
+    #[msg("Swap output is below the requested minimum")]
+    SlippageExceeded,
+    #[msg("Invoice has expired")]
+    InvoiceExpired,
+    #[msg("Invoice has already been paid")]
+    InvoiceAlreadyPaid,
+    #[msg("Invoice is unpaid and not yet expired")]
+    InvoiceOpen,
+    #[msg("Card serial does not match")]
+    InvalidCardSerial,
+    #[msg("Card redemption was not committed in an earlier slot")]
+    InvalidCardCommitment,
+    #[msg("Quote has expired")]
+    QuoteExpired,
+    #[msg("Missing or invalid ed25519 signature")]
+    InvalidSignature,
+    #[msg("Permit has expired")]
+    PermitExpired,
+    #[msg("Wallet is already an org member")]
+    OrgMemberExists,
+    #[msg("Org member list is full")]
+    OrgFull,
+    #[msg("Wallet is not an org member")]
+    NotOrgMember,
+    #[msg("Pricing variants must be unique with bonus within 100%")]
+    InvalidPricingVariant,
+    #[msg("Pricing variant is not approved")]
+    UnknownPricingVariant,
+}
+
+// Absolute deviation of the USDC/USD price from 1.0, in bps.
+fn usdc_deviation_bps(config: &Config, price_update: &PriceUpdateV2) -> Result<u64> {
+    let price = price_update.get_price_no_older_than(
+        &Clock::get()?,
+        config.depeg_max_age_secs,
+        &config.usdc_feed_id,
+    )?;
+    require!(price.exponent <= 0, CreditsError::DepegDetected);
+    let one = 10i128
+        .checked_pow(price.exponent.unsigned_abs())
+        .ok_or(CreditsError::Overflow)?;
+    let deviation = (i128::from(price.price) - one).unsigned_abs();
+    let deviation_bps = deviation
+        .checked_mul(u128::from(BPS_DENOMINATOR))
+        .ok_or(CreditsError::Overflow)?
+        / one.unsigned_abs();
+    u64::try_from(deviation_bps).map_err(|_| error!(CreditsError::Overflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialized_len<T: AnchorSerialize>(account: &T) -> usize {
+        account.try_to_vec().unwrap().len()
+    }
+
+    // Fixed-layout accounts serialize to the same length whatever their contents.
+    fn zeroed<T: AnchorDeserialize + Space>() -> T {
+        T::deserialize(&mut vec![0u8; T::INIT_SPACE].as_slice()).unwrap()
+    }
+
+    macro_rules! assert_fixed_space {
+        ($($account:ty),* $(,)?) => {
+            $(assert_eq!(
+                serialized_len(&zeroed::<$account>()),
+                <$account>::INIT_SPACE,
+                stringify!($account)
+            );)*
+        };
+    }
+
+    #[test]
+    fn serialized_sizes_match_init_space() {
+        assert_fixed_space!(
+            GlobalState,
+            Config,
+            Journal,
+            AcceptedMint,
+            Org,
+            ExternalIdLink,
+            Airdrop,
+            AirdropReceipt,
+            Subscription,
+            AutoTopUp,
+            Campaign,
+            Voucher,
+            Invoice,
+            QuoteReceipt,
+            PermitReceipt,
+            PrepaidCard,
+            CardCommit,
+            CreditPackage,
+            PriceTiers,
+            HolderTiers,
+            RegionVault,
+            SolVault,
+            Attestation,
+            Webhook,
+            PriceFeed,
+            UserCredit,
+            Manifest,
+            ManifestUsage,
+            PendingSpend,
+            Registry,
+            RegistryEntry,
+            Authorization,
+        );
+        #[cfg(feature = "devnet-faucet")]
+        assert_fixed_space!(FaucetClaim);
+
+        let mut viewer = zeroed::<Viewer>();
+        viewer.label = "x".repeat(MAX_LABEL_LEN);
+        assert_eq!(serialized_len(&viewer), Viewer::INIT_SPACE);
+    }
+
+    fn curve_config(credits_sold: u64) -> Config {
+        let mut config = zeroed::<Config>();
+        config.pricing_mode = PricingMode::Curve;
+        config.curve_base_unit = 100_000;
+        config.curve_step_credits = 10;
+        config.curve_step_bps = 1_000;
+        config.curve_max_unit = 200_000;
+        config.credits_sold = credits_sold;
+        config
+    }
+
+    #[test]
+    fn curve_purchases_integrate_across_steps() {
+        let config = curve_config(5);
+        // 5 credits left at 0.10, then 4 at 0.11; spot pricing would have given 10.
+        assert_eq!(credits_for_usd(&config, 1_000_000).unwrap(), (9, 60_000));
+        assert_eq!(usd_for_credits(&config, 9).unwrap(), 940_000);
+
+        // Past the cap the price stops moving, however large the purchase.
+        let capped = curve_config(1_000);
+        assert_eq!(current_credit_unit(&capped).unwrap(), 200_000);
+        assert_eq!(credits_for_usd(&capped, 1_000_000_000).unwrap(), (5_000, 0));
+
+        let mut flat = zeroed::<Config>();
+        flat.credit_unit = 100_000;
+        assert_eq!(credits_for_usd(&flat, 250_000).unwrap(), (2, 50_000));
+    }
+
+    #[test]
+    fn only_sold_credits_move_the_curve() {
+        let mut config = curve_config(5);
+        assert!(!record_sale(&mut config, 20, 3).unwrap());
+        assert_eq!(config.credits_sold, 8);
+        assert_eq!(config.total_credits_issued, 20);
+        assert!(record_sale(&mut config, 2, 2).unwrap());
+        assert_eq!(current_credit_unit(&config).unwrap(), 110_000);
+    }
 }